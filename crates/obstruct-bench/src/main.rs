@@ -0,0 +1,192 @@
+//! Compare the compile time of anonymous structs created by `instruct!` with that
+//! of the former expansion, which defined one struct and four impls per field.
+//!
+//! Usage (requires the same nightly toolchain as `obstruct`):
+//!
+//! ```text
+//! cargo +nightly run -p obstruct-bench --release -- [--calls N] [--fields N] [--out DIR]
+//! ```
+//!
+//! For each design, this generates a crate under `DIR` (by default `target/obstruct-bench`)
+//! containing `calls` functions, each of which creates an anonymous struct with `fields`
+//! fields, then measures `cargo check` on that crate, once its dependencies are built.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// The number of times we measure each design. We report the best time.
+const RUNS: usize = 3;
+
+struct Options {
+    /// The number of uses of `instruct!`.
+    calls: usize,
+
+    /// The number of fields in each anonymous struct.
+    fields: usize,
+
+    /// The directory in which to generate the crates.
+    out: PathBuf,
+}
+impl Options {
+    fn parse() -> Result<Self, String> {
+        let mut options = Options {
+            calls: 500,
+            fields: 8,
+            out: PathBuf::from("target/obstruct-bench"),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("Missing value for {arg}"))?;
+            match arg.as_str() {
+                "--calls" => options.calls = value.parse().map_err(|e| format!("Invalid --calls: {e}"))?,
+                "--fields" => options.fields = value.parse().map_err(|e| format!("Invalid --fields: {e}"))?,
+                "--out" => options.out = PathBuf::from(value),
+                _ => return Err(format!("Unknown option {arg}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A design for the expansion of `instruct!`.
+#[derive(Clone, Copy)]
+enum Design {
+    /// The current expansion: `(obstruct::Named::<"f0", _>(0), (...))`.
+    Named,
+
+    /// The former expansion: each field is a local struct implementing `Field` and `OwnedField`.
+    LocalStructs,
+}
+impl Design {
+    fn name(self) -> &'static str {
+        match self {
+            Design::Named => "named",
+            Design::LocalStructs => "local-structs",
+        }
+    }
+
+    /// Generate the source of a function creating an anonymous struct with `fields` fields.
+    fn function(self, index: usize, fields: usize) -> String {
+        let mut body = String::new();
+        match self {
+            Design::Named => {
+                body.push_str("obstruct::instruct! { ");
+                for field in 0..fields {
+                    write!(body, "f{field}: {field}, ").unwrap();
+                }
+                body.push('}');
+            }
+            Design::LocalStructs => {
+                for field in 0..fields {
+                    write!(body, "(match {field} {{ value => {{ {} }} }}, ", local_struct(&format!("f{field}"))).unwrap();
+                }
+                body.push_str("()");
+                body.push_str(&")".repeat(fields));
+            }
+        }
+        format!("pub fn make_{index}() -> impl Sized {{\n    {body}\n}}\n")
+    }
+}
+
+/// The former expansion of a field `name` with value `value`, without the outer `match`.
+fn local_struct(name: &str) -> String {
+    format!(r#"
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        struct {name}<T>(T);
+        impl<T> obstruct::Field<T> for {name}<T> {{
+            const NAME: &'static str = "{name}";
+            fn take(self) -> T {{ self.0 }}
+        }}
+        impl<'a, T> obstruct::Field<&'a T> for &'a {name}<T> {{
+            const NAME: &'static str = "{name}";
+            fn take(self) -> &'a T {{ &self.0 }}
+        }}
+        impl<'a, T> obstruct::Field<&'a mut T> for &'a mut {name}<T> {{
+            const NAME: &'static str = "{name}";
+            fn take(self) -> &'a mut T {{ &mut self.0 }}
+        }}
+        impl<T> obstruct::OwnedField for {name}<T> {{
+            const NAME: &'static str = "{name}";
+            type Value = T;
+            fn get(&self) -> &T {{ &self.0 }}
+            fn get_mut(&mut self) -> &mut T {{ &mut self.0 }}
+        }}
+        {name}(value)
+    "#)
+}
+
+/// Generate the benchmark crate for `design` in `dir`.
+fn generate(design: Design, options: &Options, dir: &Path) -> std::io::Result<()> {
+    let obstruct = Path::new(env!("CARGO_MANIFEST_DIR")).join("../obstruct").canonicalize()?;
+    std::fs::create_dir_all(dir.join("src"))?;
+    std::fs::write(dir.join("Cargo.toml"), format!(r#"[package]
+name = "obstruct-bench-{name}"
+version = "0.0.0"
+edition = "2021"
+publish = false
+
+[dependencies]
+obstruct = {{ path = {obstruct:?} }}
+
+# Not part of the obstruct workspace.
+[workspace]
+"#, name = design.name()))?;
+
+    let mut source = String::from("#![feature(adt_const_params, unsized_const_params)]\n#![allow(incomplete_features)]\n\n");
+    for index in 0..options.calls {
+        source.push_str(&design.function(index, options.fields));
+    }
+    std::fs::write(dir.join("src/lib.rs"), source)
+}
+
+/// Run `cargo check` in `dir`, returning how long it took.
+fn check(dir: &Path) -> Result<Duration, String> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let start = Instant::now();
+    let status = Command::new(cargo)
+        .arg("check")
+        .arg("--quiet")
+        .current_dir(dir)
+        // Don't inherit the toolchain of the benchmark itself, e.g. if it was built with stable.
+        .env_remove("RUSTC")
+        .status()
+        .map_err(|e| format!("Could not run cargo: {e}"))?;
+    if !status.success() {
+        return Err(format!("cargo check failed in {}", dir.display()));
+    }
+    Ok(start.elapsed())
+}
+
+fn bench(design: Design, options: &Options) -> Result<Duration, String> {
+    let dir = options.out.join(design.name());
+    generate(design, options, &dir).map_err(|e| format!("Could not generate {}: {e}", dir.display()))?;
+
+    // Build dependencies, which we don't want to measure.
+    check(&dir)?;
+
+    let lib = dir.join("src/lib.rs");
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        // Force cargo to check the crate again.
+        let source = std::fs::read(&lib).map_err(|e| e.to_string())?;
+        std::fs::write(&lib, source).map_err(|e| e.to_string())?;
+        best = best.min(check(&dir)?);
+    }
+    Ok(best)
+}
+
+fn main() -> Result<(), String> {
+    let options = Options::parse()?;
+    println!("{} uses of instruct!, with {} fields each", options.calls, options.fields);
+    let mut results = vec![];
+    for design in [Design::LocalStructs, Design::Named] {
+        let duration = bench(design, &options)?;
+        println!("{:>14}: {:.2}s", design.name(), duration.as_secs_f64());
+        results.push(duration);
+    }
+    println!("{:>14}: {:.1}x", "speedup", results[0].as_secs_f64() / results[1].as_secs_f64());
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! `#[cfg]` attributes on fields and named arguments.
+//!
+//! Macros can't evaluate `cfg` predicates, so we attach them to the code generated for
+//! each field, e.g. a `let` statement, for the compiler to evaluate them.
+
+use quote::{quote, quote_spanned, TokenStreamExt};
+use syn::Attribute;
+
+use crate::name::{self, FieldName};
+
+pub fn is_cfg(attr: &Attribute) -> bool {
+    attr.path().is_ident("cfg")
+}
+
+/// The conjunction of the `#[cfg]` attributes among `attrs`, e.g. `all(unix, feature = "gpu")`,
+/// if there is any.
+pub fn predicate(attrs: &[Attribute]) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    let predicates = attrs.iter()
+        .filter(|attr| is_cfg(attr))
+        .map(|attr| Ok(attr.meta.require_list()?.tokens.clone()))
+        .collect::<syn::Result<Vec<_>>>()?;
+    if predicates.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(quote!{ all(#(#predicates),*) }))
+}
+
+/// Check that no name appears twice in any configuration, as `name::check_for_duplicates`.
+///
+/// Each name comes with the attributes of its field. A name may appear several times with
+/// distinct `#[cfg]`s, e.g. `#[cfg(unix)] fd` and `#[cfg(windows)] fd`, but not twice with
+/// the same `#[cfg]`, nor with and without a `#[cfg]`, e.g. `#[cfg(unix)] fd` and `fd`.
+///
+/// We can't tell whether distinct `#[cfg]`s exclude each other, e.g. `#[cfg(unix)]` and
+/// `#[cfg(target_os = "linux")]` don't, so we return statements (or items) for the compiler
+/// to check, e.g. `#[cfg(all(all(unix), all(target_os = "linux")))] compile_error!(...);`.
+pub fn check_for_duplicates<'a>(names: impl IntoIterator<Item = (&'a FieldName, &'a [Attribute])>, kind: &str) -> syn::Result<proc_macro2::TokenStream> {
+    let names = names.into_iter()
+        .map(|(name, attrs)| Ok((name, predicate(attrs)?)))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let names: Vec<_> = names.iter()
+        .map(|(name, predicate)| (*name, predicate.as_ref().map(|predicate| predicate.to_string()), predicate))
+        .collect();
+    let unconditional = |predicate: &Option<String>| predicate.is_none();
+    name::check_for_duplicates(names.iter().filter(|(_, predicate, _)| unconditional(predicate)).map(|(name, _, _)| *name), kind)?;
+    // With each predicate, the names enabled by this predicate and the unconditional ones.
+    let mut errors: Option<syn::Error> = None;
+    let mut checked: Vec<&str> = vec![];
+    for enabled in names.iter().filter_map(|(_, predicate, _)| predicate.as_deref()) {
+        if checked.contains(&enabled) {
+            continue;
+        }
+        checked.push(enabled);
+        let names = names.iter()
+            .filter(|(_, predicate, _)| predicate.as_deref().is_none_or(|predicate| predicate == enabled))
+            .map(|(name, _, _)| *name);
+        if let Err(error) = name::check_for_duplicates(names, kind) {
+            match errors {
+                None => errors = Some(error),
+                Some(ref mut errors) => errors.combine(error),
+            }
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+    // Names with distinct predicates, which may both hold.
+    let mut overlaps = proc_macro2::TokenStream::new();
+    for (i, (name, string, predicate)) in names.iter().enumerate() {
+        let Some(predicate) = predicate else {
+            continue;
+        };
+        for (_, _, other) in names[..i].iter().filter(|(other, other_string, _)| other == name && other_string != string) {
+            let message = format!("{kind} `{name}` is defined twice");
+            overlaps.append_all(quote_spanned!{name.span()=>
+                #[cfg(all(#other, #predicate))]
+                ::core::compile_error!(#message);
+            });
+        }
+    }
+    Ok(overlaps)
+}
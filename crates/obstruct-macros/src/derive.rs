@@ -0,0 +1,117 @@
+//! Implementation of `#[derive(Obstruct)]`, to convert between named structs and anonymous structs.
+
+use std::iter::zip;
+
+use itertools::Itertools;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Path};
+
+use crate::krate::{self, OBSTRUCT};
+use crate::name::FieldName;
+use crate::repr;
+
+/// A named struct deriving `Obstruct`, e.g. `#[derive(Obstruct)] struct Foo { x: u32, y: f64 }`.
+pub struct DeriveObstruct(DeriveInput);
+impl DeriveObstruct {
+    pub fn new(input: DeriveInput) -> Self {
+        DeriveObstruct(input)
+    }
+
+    /// Emit the conversions, using the path to `obstruct` given by `#[obstruct(crate = path)]`,
+    /// if any.
+    pub fn transform(self) -> TokenStream {
+        match self.crate_path() {
+            Ok(Some(path)) => krate::with_path(quote!{ #path }, || self.emit()),
+            Ok(None) => self.emit(),
+            Err(error) => error.to_compile_error().into(),
+        }
+    }
+
+    /// The path given by `#[obstruct(crate = path)]`, if any.
+    fn crate_path(&self) -> syn::Result<Option<Path>> {
+        let mut path = None;
+        for attr in self.0.attrs.iter().filter(|attr| attr.path().is_ident("obstruct")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    path = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("Expected `crate = path`"))
+                }
+            })?;
+        }
+        Ok(path)
+    }
+
+    /// Emit the conversions between this struct and the matching anonymous struct.
+    ///
+    /// We rewrite `#[derive(Obstruct)] struct Foo { y: f64, x: u32 }` into
+    ///
+    /// ```ignore
+    /// impl obstruct::IntoAnonymous for Foo {
+    ///     type Anonymous = (obstruct::Named<"x", u32>, (obstruct::Named<"y", f64>, ()));
+    ///     fn into_anonymous(self) -> Self::Anonymous {
+    ///         (obstruct::Named(self.x), (obstruct::Named(self.y), ()))
+    ///     }
+    /// }
+    /// impl From<Foo> for (obstruct::Named<"x", u32>, (obstruct::Named<"y", f64>, ())) {
+    ///     fn from(value: Foo) -> Self {
+    ///         obstruct::IntoAnonymous::into_anonymous(value)
+    ///     }
+    /// }
+    ///
+    /// // Including anonymous structs produced by `instruct!`.
+    /// impl From<(obstruct::Named<"x", u32>, (obstruct::Named<"y", f64>, ()))> for Foo {
+    ///     fn from((obstruct::Named(x), (obstruct::Named(y), ())): (obstruct::Named<"x", u32>, (obstruct::Named<"y", f64>, ()))) -> Self {
+    ///         Foo { x, y }
+    ///     }
+    /// }
+    /// ```
+    fn emit(self) -> TokenStream {
+        let input = self.0;
+        let Data::Struct(ref data) = input.data else {
+            return syn::Error::new_spanned(&input, "`Obstruct` can only be derived for structs").to_compile_error().into();
+        };
+        let Fields::Named(ref fields) = data.fields else {
+            return syn::Error::new_spanned(&input, "`Obstruct` can only be derived for structs with named fields").to_compile_error().into();
+        };
+
+        // Normalize order, as in `instruct!`.
+        let fields: Vec<_> = fields.named.iter()
+            .sorted_by_cached_key(|field| FieldName::from(field.ident.clone().unwrap()))
+            .collect();
+        let idents: Vec<_> = fields.iter().map(|field| field.ident.as_ref().unwrap()).collect();
+        let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+        let field_names: Vec<_> = idents.iter().map(|&ident| FieldName::from(ident.clone()).lit()).collect();
+
+        let ident = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+        let anonymous = repr::nest(zip(&field_names, &types).map(|(field_name, ty)| quote!{ #OBSTRUCT::Named<#field_name, #ty> }));
+        let values = repr::nest(idents.iter().map(|ident| quote!{ #OBSTRUCT::Named(self.#ident) }));
+        let pattern = repr::nest(idents.iter().map(|ident| quote!{ #OBSTRUCT::Named(#ident) }));
+
+        quote!{
+            impl #impl_generics #OBSTRUCT::IntoAnonymous for #ident #ty_generics #where_clause {
+                type Anonymous = #anonymous;
+                fn into_anonymous(self) -> Self::Anonymous {
+                    #values
+                }
+            }
+
+            impl #impl_generics ::core::convert::From<#ident #ty_generics> for #anonymous #where_clause {
+                fn from(value: #ident #ty_generics) -> Self {
+                    #OBSTRUCT::IntoAnonymous::into_anonymous(value)
+                }
+            }
+
+            impl #impl_generics ::core::convert::From<#anonymous> for #ident #ty_generics #where_clause {
+                fn from(#pattern: #anonymous) -> Self {
+                    #ident {
+                        #(#idents,)*
+                    }
+                }
+            }
+        }.into()
+    }
+}
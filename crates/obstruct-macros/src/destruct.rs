@@ -2,56 +2,74 @@ use std::iter::zip;
 
 use itertools::Itertools;
 use proc_macro::TokenStream;
-use quote::{quote, format_ident, TokenStreamExt};
-use syn::{parse::Parse, Token, Ident, Expr, braced, parenthesized, ReturnType, Block, FieldsNamed, Generics, TypeParam, LitStr, Pat, punctuated::Punctuated, parse_macro_input, parse_quote};
+use proc_macro2::Span;
+use quote::{quote, quote_spanned, format_ident, TokenStreamExt};
+use syn::{parse::Parse, Token, Attribute, Ident, Expr, braced, parenthesized, ReturnType, Block, Generics, LitStr, Pat, Visibility, punctuated::Punctuated, parse_macro_input, parse_quote, parse_quote_spanned};
+
+use crate::krate::{self, OBSTRUCT};
+use crate::{cfg, param_attrs};
+use crate::name::{FieldName, GenericNames};
+
+/// The pattern used to destructure a field, e.g. `foo: (a, b)` or `foo: { bar, sna }`.
+enum DestructPattern {
+    /// A regular Rust pattern.
+    Pat(Pat),
+
+    /// A nested anonymous struct pattern.
+    Nested(DestructFields),
+}
 
 struct DestructField {
+    /// `#[cfg]` attributes, if any.
+    attrs: Vec<Attribute>,
     maybe_ref: Option<Token![ref]>,
     maybe_mut: Option<Token![mut]>,
-    ident: Ident,
-    maybe_pat: Option<Pat>,
+    name: FieldName,
+    maybe_pat: Option<DestructPattern>,
 }
 impl DestructField {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        if let Some(attr) = attrs.iter().find(|attr| !cfg::is_cfg(attr)) {
+            return Err(syn::Error::new_spanned(attr, "unsupported attribute, expected `#[cfg]`"));
+        }
         let maybe_ref: Option<Token![ref]> = input.parse()?;
         let maybe_mut: Option<Token![mut]> = input.parse()?;
-        let ident: Ident = input.parse()?;
+        let name: FieldName = input.parse()?;
         let maybe_colon: Option<Token![:]> = input.parse()?;
-        let maybe_pat = if maybe_colon.is_some() {
-            Some(Pat::parse_multi_with_leading_vert(input)?)
-        } else {
+        let maybe_pat = if maybe_colon.is_none() {
+            // Shorthand `foo` binds variable `foo`.
+            name.to_ident("pattern")?;
             None
+        } else if input.peek(syn::token::Brace) {
+            let braces_content;
+            braced!(braces_content in input);
+            Some(DestructPattern::Nested(braces_content.parse()?))
+        } else {
+            Some(DestructPattern::Pat(Pat::parse_multi_with_leading_vert(input)?))
         };
         Ok(Self {
+            attrs,
             maybe_ref,
             maybe_mut,
-            ident,
+            name,
             maybe_pat
         })
     }
 }
 
 struct DestructFields {
-    pats: Punctuated<DestructField, Token![,]>
+    pats: Punctuated<DestructField, Token![,]>,
+
+    /// Statements that check that no field is destructured twice, see `cfg::check_for_duplicates`.
+    overlaps: proc_macro2::TokenStream,
 }
 impl Parse for DestructFields {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let pats = input.parse_terminated(DestructField::parse, Token![,])?;
+        let overlaps = cfg::check_for_duplicates(pats.iter().map(|pat| (&pat.name, &pat.attrs[..])), "field")?;
 
-        // Reject if there are duplicate assignments.
-        let mut prev = None;
-        for pat in &pats {
-            if let Some(prev) = prev {
-                if prev == pat.ident {
-                    return Err(syn::Error::new(
-                        pat.ident.span(),
-                        format!("Duplicate field {}", pat.ident)))
-                    }
-            }
-            prev = Some(pat.ident.clone());
-        }
-
-        Ok(DestructFields { pats })
+        Ok(DestructFields { pats, overlaps })
     }
 }
 
@@ -61,7 +79,7 @@ pub enum Destruct {
 }
 impl Parse for Destruct {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if input.peek(Token![fn]) {
+        if input.peek(Token![fn]) || input.peek(Token![#]) {
             DestructFunction::parse(input).map(Destruct::DestructFunction)
         } else {
             DestructExpression::parse(input).map(Destruct::DestructExpression)
@@ -101,62 +119,215 @@ impl Parse for DestructExpression {
 }
 impl DestructExpression {
     fn transform(self) -> TokenStream {
-        let fields: Vec<_> = self.fields.pats.into_iter()
-            .sorted_by_cached_key(|field| field.ident.to_string())
-            .collect();
         let expr = self.expr;
-        let maybe_ref = self.maybe_ref;
-        let declarations = fields.iter().map(|field| {
-            let ident = &field.ident;
-            let maybe_ref = maybe_ref.or(field.maybe_ref);
-            let maybe_mut = if maybe_ref.is_some() { // Here, we need `mut` to show up only if we're in a `ref.`
-                field.maybe_mut
-            } else {
-                None
-            };
+        Self::destructure(self.fields, self.maybe_ref, quote!{ #expr }, false, 0, &[]).into()
+    }
+
+    /// Emit the code to destructure `expr` into `fields`.
+    ///
+    /// We rewrite `destruct!{let {a, ref b} = expr}` into
+    ///
+    /// ```ignore
+    /// let obstruct_struct_0 = obstruct::IntoAnonymous::into_anonymous(expr);
+    /// // For error messages, which list the fields of `expr`.
+    /// let obstruct_whole_0 = obstruct::FieldsOf::of(&obstruct_struct_0);
+    ///
+    /// // Fields destructured by value are removed first.
+    /// let (obstruct_0_a, obstruct_struct_0) = obstruct::RemoveField::<"a", _, _, _, _>::remove_field(obstruct_struct_0, &obstruct_whole_0);
+    ///
+    /// // Then fields destructured by reference are removed from the borrowed remaining fields.
+    /// let obstruct_owner_0 = obstruct_struct_0;
+    /// let obstruct_struct_0 = obstruct::AsRefs::as_refs(&obstruct_owner_0);
+    /// let (obstruct_0_b, obstruct_struct_0) = obstruct::RemoveField::<"b", _, _, _, _>::remove_field(obstruct_struct_0, &obstruct_whole_0);
+    ///
+    /// // There must be no other field.
+    /// obstruct::NoOtherField::no_other_field(obstruct_struct_0);
+    ///
+    /// let a = obstruct_0_a;
+    /// let b = obstruct_0_b;
+    /// ```
+    ///
+    /// As fields are looked up by name, the order of fields in `expr` doesn't matter. If all
+    /// fields are destructured by reference, `expr` is borrowed rather than moved.
+    ///
+    /// Nested anonymous struct patterns are destructured recursively, checking field names
+    /// at each level. `expr_is_ref` specifies whether `expr` is already a reference, which
+    /// happens when destructuring a nested anonymous struct by reference, and `depth` is
+    /// the level of nesting, used to keep internal names distinct.
+    ///
+    /// Each statement is preceded by `cfgs`, the `#[cfg]`s of the enclosing fields, and the
+    /// statements of a field by its own `#[cfg]`s, e.g. `#[cfg(unix)] fd`, so that disabled
+    /// fields are neither looked up nor bound.
+    fn destructure(fields: DestructFields, maybe_ref: Option<Token![ref]>, expr: proc_macro2::TokenStream, expr_is_ref: bool, depth: usize, cfgs: &[Attribute]) -> proc_macro2::TokenStream {
+        let overlaps = fields.overlaps;
+        let fields: Vec<_> = fields.pats.into_iter()
+            .map(|field| DestructField { maybe_ref: maybe_ref.or(field.maybe_ref), ..field })
+            .collect();
+        // Whether we need to borrow fields mutably.
+        let mutable = fields.iter().any(|field| field.maybe_ref.is_some() && field.maybe_mut.is_some());
+        let value_ident = |field: &DestructField| format_ident!("obstruct_{}_{}", depth, field.name, span = Span::mixed_site());
+        let struct_ident = format_ident!("obstruct_struct_{}", depth, span = Span::mixed_site());
+        let owner_ident = format_ident!("obstruct_owner_{}", depth, span = Span::mixed_site());
+        let whole_ident = format_ident!("obstruct_whole_{}", depth, span = Span::mixed_site());
+        let (by_ref, by_value): (Vec<_>, Vec<_>) = fields.iter().partition(|field| field.maybe_ref.is_some());
+        let remove = |field: &DestructField| {
+            let value = value_ident(field);
+            let field_name = field.name.lit();
+            let obstruct = krate::path_at(field.name.span());
+            let attrs = &field.attrs;
+            // Errors, e.g. on unknown fields, point at the field. Internal names are located
+            // within the macro, so the receiver is wrapped in a block located at the field.
+            quote_spanned!{field.name.span()=>
+                #(#cfgs)*
+                #(#attrs)*
+                #[allow(unused_braces)]
+                let (#value, #struct_ident) = #obstruct::RemoveField::<#field_name, _, _, _, _>::remove_field(&#whole_ident, { #struct_ident });
+            }
+        };
+
+        let mut tokens = if overlaps.is_empty() {
+            proc_macro2::TokenStream::new()
+        } else {
             quote!{
-                #maybe_ref #maybe_mut #ident
+                #(#cfgs)*
+                { #overlaps }
             }
-        });
-        let mut tokens = quote!{
-            let (#(#declarations),*) = #expr;
         };
-        let assigns: Vec<_> = fields.into_iter().map(|field| {
-            let ident = field.ident;
-            let field_name = LitStr::new(&ident.to_string(), ident.span());
-            let maybe_mut = if field.maybe_ref.is_some() { // Here, we need `mut` to show up only if we're NOT in a `ref`.
-                None
-            } else {
-                field.maybe_mut
+        tokens.append_all(if by_value.is_empty() {
+            // Borrow `expr`, rather than moving it.
+            // Binding the reference extends the lifetime of `expr` if it is a temporary.
+            let borrowed = match (expr_is_ref, mutable) {
+                (true, _) => expr,
+                (false, true) => quote!{ &mut #expr },
+                (false, false) => quote!{ &#expr },
             };
-            let assertion_arg = if maybe_ref.is_some() || field.maybe_ref.is_some() {
-                quote!(#ident)
+            if mutable {
+                quote!{
+                    #(#cfgs)*
+                    let #owner_ident = #borrowed;
+                    #(#cfgs)*
+                    let #struct_ident = #OBSTRUCT::AsMuts::as_muts(#owner_ident);
+                    #(#cfgs)*
+                    let #whole_ident = #OBSTRUCT::FieldsOf::of(&#struct_ident);
+                }
             } else {
-                quote!(&#ident)
+                quote!{
+                    #(#cfgs)*
+                    let #owner_ident = #borrowed;
+                    #(#cfgs)*
+                    let #struct_ident = #OBSTRUCT::AsRefs::as_refs(#owner_ident);
+                    #(#cfgs)*
+                    let #whole_ident = #OBSTRUCT::FieldsOf::of(&#struct_ident);
+                }
+            }
+        } else {
+            // `expr` may also be a struct with `#[derive(Obstruct)]`, which we need to convert first.
+            let mut tokens = quote!{
+                #(#cfgs)*
+                let #struct_ident = #OBSTRUCT::IntoAnonymous::into_anonymous(#expr);
+                #(#cfgs)*
+                let #whole_ident = #OBSTRUCT::FieldsOf::of(&#struct_ident);
             };
-            let pattern = match field.maybe_pat {
-                Some(pat) => quote!{ #pat },
-                None => quote!{ #ident }
+            tokens.append_all(by_value.iter().map(|field| remove(field)));
+            if !by_ref.is_empty() {
+                tokens.append_all(if mutable {
+                    quote!{
+                        #(#cfgs)*
+                        let mut #owner_ident = #struct_ident;
+                        #(#cfgs)*
+                        let #struct_ident = #OBSTRUCT::AsMuts::as_muts(&mut #owner_ident);
+                    }
+                } else {
+                    quote!{
+                        #(#cfgs)*
+                        let #owner_ident = #struct_ident;
+                        #(#cfgs)*
+                        let #struct_ident = #OBSTRUCT::AsRefs::as_refs(&#owner_ident);
+                    }
+                });
+            }
+            tokens
+        });
+        tokens.append_all(by_ref.iter().map(|field| remove(field)));
+        tokens.append_all(quote!{
+            // Won't compile if `expr` has other fields.
+            #(#cfgs)*
+            #OBSTRUCT::NoOtherField::no_other_field(#struct_ident);
+        });
+
+        let assigns: Vec<_> = fields.into_iter().map(|field| {
+            let value = value_ident(&field);
+            let is_ref = field.maybe_ref.is_some();
+            let value = if is_ref && mutable && field.maybe_mut.is_none() {
+                // We have borrowed mutably, but this field should be borrowed immutably.
+                quote!{ &*#value }
+            } else {
+                quote!{ #value }
             };
-            quote!{
-                {
-                    fn assert_type<T, U>(_: &T) where T: obstruct::Field::<U, NAME=#field_name> {
-                        // Won't compile if we have the wrong type.
+            let cfgs: Vec<_> = cfgs.iter().chain(&field.attrs).cloned().collect();
+            match field.maybe_pat {
+                Some(DestructPattern::Nested(nested)) => {
+                    let maybe_ref = if is_ref { Some(Token![ref](field.name.span())) } else { None };
+                    Self::destructure(nested, maybe_ref, value, is_ref, depth + 1, &cfgs)
+                }
+                Some(DestructPattern::Pat(pat)) => quote!{
+                    #(#cfgs)*
+                    let #pat = #value;
+                },
+                None => {
+                    // Checked while parsing.
+                    let ident = field.name.ident().unwrap();
+                    // With `ref`, `mut` means that the reference is mutable, not the binding.
+                    let maybe_mut = if is_ref { None } else { field.maybe_mut };
+                    quote!{
+                        #(#cfgs)*
+                        let #maybe_mut #ident = #value;
                     }
-                    assert_type(#assertion_arg)
                 }
-                let #maybe_mut #pattern = obstruct::Field::<_>::take(#ident);
             }
         }).collect();
         tokens.append_all(assigns);
-        eprintln!("YORIC: Generated {}", tokens);
-        tokens.into()
+        tokens
+    }
+}
+
+
+/// A named parameter of a destruct function, e.g. `a: u8` or, with a default value, `a: u8 = 8`.
+struct NamedParam {
+    field: syn::Field,
+    default: Option<Expr>,
+}
+impl Parse for NamedParam {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let field = input.call(syn::Field::parse_named)?;
+        let default = if input.parse::<Option<Token![=]>>()?.is_some() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(NamedParam { field, default })
+    }
+}
+impl NamedParam {
+    fn name(&self) -> FieldName {
+        FieldName::from(self.field.ident.clone().unwrap())
     }
 }
 
+/// The maximal number of distinct `#[cfg]` predicates on the named arguments of a function,
+/// which `DestructFunction::transform` emits in `2^MAX_CFG_PREDICATES` versions.
+const MAX_CFG_PREDICATES: usize = 4;
 
-/// A destruct function declaration, e.g. `destruct!{fn foo([self,] { a: 8, b: 9 }}`
+/// A destruct function declaration, e.g. `destruct!{fn foo([self,] { a: u8, b: u16 }) { ... }}` or,
+/// with default values, aliases or deprecated named arguments, `destruct!{#[params] fn foo({ a: u8, b: u16 = 9 }) { ... }}`.
 pub struct DestructFunction {
+    /// Whether to declare a companion module describing the named arguments, for
+    /// `call!{ #[params] foo, {...} }`.
+    with_params: bool,
+
+    /// Visibility of the function, e.g. `pub`.
+    vis: Visibility,
+
     /// Function identifier.
     ident: Ident,
 
@@ -169,14 +340,24 @@ pub struct DestructFunction {
     maybe_comma: Option<Token![,]>,
 
     /// Actual arguments.
-    bindings: FieldsNamed,
+    bindings: Vec<NamedParam>,
+
+    /// If specified, `..`, i.e. the function accepts any anonymous struct that has the
+    /// named arguments, ignoring any other field.
+    maybe_rest: Option<Token![..]>,
 
     return_type: ReturnType,
     body: Block,
+
+    /// Items that check that no named argument is declared twice, see `cfg::check_for_duplicates`.
+    overlaps: proc_macro2::TokenStream,
 }
 impl Parse for DestructFunction {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        // fn foo([self,] AnonymousStructDeclaration)
+        // [#[params]] [pub] fn foo([self,] AnonymousStructDeclaration)
+        let attrs = input.call(Attribute::parse_outer)?;
+        let with_params = param_attrs::params(&attrs)?;
+        let vis: Visibility = input.parse()?;
         input.parse::<Token![fn]>()?;     // `fn`
         let ident: Ident = input.parse()?;// function name
 
@@ -188,9 +369,52 @@ impl Parse for DestructFunction {
         // Optional `self`.
         let maybe_self = paren_content.parse::<Option<Token![self]>>()?;
         let maybe_comma = paren_content.parse::<Option<Token![,]>>()?;
+        if with_params && maybe_self.is_some() {
+            // The companion module can't be declared in an `impl`.
+            return Err(syn::Error::new_spanned(&attrs[0], "`#[params]` is not supported on methods"));
+        }
 
-        // Named arguments.
-        let bindings = paren_content.parse::<FieldsNamed>()?;
+        // Named arguments, optionally followed by `..`.
+        let braces_content;
+        braced!(braces_content in paren_content);
+        let mut bindings: Vec<NamedParam> = vec![];
+        let mut maybe_rest = None;
+        while !braces_content.is_empty() {
+            if braces_content.peek(Token![..]) {
+                maybe_rest = Some(braces_content.parse()?);
+                break;
+            }
+            bindings.push(braces_content.parse()?);
+            if braces_content.is_empty() {
+                break;
+            }
+            braces_content.parse::<Token![,]>()?;
+        }
+        // Names and aliases must be distinct in each configuration. With `#[cfg]`s, e.g.
+        // `#[cfg(unix)] fd: RawFd` and `#[cfg(windows)] fd: RawHandle`, a name may appear twice.
+        let mut names = vec![];
+        for param in &bindings {
+            names.push((param.name(), &param.field.attrs[..]));
+            names.extend(param_attrs::aliases(&param.field.attrs)?.into_iter().map(|alias| (alias, &param.field.attrs[..])));
+        }
+        let overlaps = cfg::check_for_duplicates(names.iter().map(|(name, attrs)| (name, *attrs)), "named argument")?;
+        if !with_params {
+            // Without the companion module, `call!` couldn't know about them.
+            let mut errors = bindings.iter().flat_map(|param| {
+                let default = param.default.as_ref().map(|default| syn::Error::new_spanned(default, "default values require `#[params]` on the function"));
+                let attrs = param.field.attrs.iter()
+                    .filter(|attr| param_attrs::is_param_attr(attr))
+                    .map(|attr| {
+                        let path = attr.path();
+                        syn::Error::new_spanned(attr, format!("`#[{}]` requires `#[params]` on the function", quote!{ #path }))
+                    });
+                default.into_iter().chain(attrs)
+            });
+            if let Some(mut error) = errors.next() {
+                error.extend(errors);
+                return Err(error);
+            }
+        }
 
         // Out of parens.
         // Optional result type.
@@ -200,69 +424,206 @@ impl Parse for DestructFunction {
         // Function body.
         let body = input.parse::<Block>()?;
 
-        Ok(DestructFunction { ident, generics, maybe_self, maybe_comma, bindings, return_type, body })
+        Ok(DestructFunction { with_params, vis, ident, generics, maybe_self, maybe_comma, bindings, maybe_rest, return_type, body, overlaps })
     }
 }
 
 
 impl DestructFunction {
+    /// Emit the function.
+    ///
+    /// Named arguments with `#[cfg]` attributes may or may not exist, depending on a
+    /// configuration that we can't evaluate. So, for each combination of the predicates of
+    /// these `#[cfg]`s, we emit a version of the function (and of its companion module, if
+    /// any), enabled if and only if exactly these predicates hold. E.g. with
+    /// `{#[cfg(unix)] fd: RawFd, path: &Path}`, we emit `#[cfg(all(all(unix)))] fn f(...)`,
+    /// with named arguments `fd` and `path`, and `#[cfg(all(not(all(unix))))] fn f(...)`,
+    /// with named argument `path`.
+    ///
+    /// Named arguments with the same predicate are enabled together, so with `n` distinct
+    /// predicates, we emit `2^n` versions of the function, of which the compiler keeps one.
+    /// To keep this reasonable, `n` is at most `MAX_CFG_PREDICATES`.
     fn transform(self) -> TokenStream {
+        let predicates = match self.bindings.iter().map(|param| cfg::predicate(&param.field.attrs)).collect::<syn::Result<Vec<_>>>() {
+            Ok(predicates) => predicates,
+            Err(error) => return error.to_compile_error().into(),
+        };
+        // The distinct predicates, compared as written.
+        let distinct: Vec<_> = predicates.iter()
+            .flatten()
+            .unique_by(|predicate| predicate.to_string())
+            .collect();
+        if distinct.is_empty() {
+            return self.emit(&self.bindings.iter().collect::<Vec<_>>(), None).into();
+        }
+        if let Some(predicate) = distinct.get(MAX_CFG_PREDICATES) {
+            // Point at the first named argument with this predicate.
+            let (param, _) = zip(&self.bindings, &predicates)
+                .find(|(_, other)| other.as_ref().is_some_and(|other| other.to_string() == predicate.to_string()))
+                .unwrap();
+            let attrs = param.field.attrs.iter().filter(|attr| cfg::is_cfg(attr));
+            return syn::Error::new_spanned(quote!{ #(#attrs)* }, format!(
+                "at most {MAX_CFG_PREDICATES} distinct `#[cfg]`s are supported on named arguments, as each combination requires a version of the function",
+            )).to_compile_error().into();
+        }
+        let mut tokens = self.overlaps.clone();
+        for enabled in distinct.iter().powerset() {
+            let enabled: Vec<String> = enabled.iter().map(|predicate| predicate.to_string()).collect();
+            let params: Vec<_> = zip(&self.bindings, &predicates)
+                .filter(|(_, predicate)| predicate.as_ref().is_none_or(|predicate| enabled.contains(&predicate.to_string())))
+                .map(|(param, _)| param)
+                .collect();
+            let cfg = distinct.iter().map(|predicate| {
+                if enabled.contains(&predicate.to_string()) {
+                    quote!{ #predicate }
+                } else {
+                    quote!{ not(#predicate) }
+                }
+            });
+            tokens.append_all(self.emit(&params, Some(quote!{ #[cfg(all(#(#cfg),*))] })));
+        }
+        tokens.into()
+    }
+
+    /// Emit the function, with named arguments `params` and attribute `cfg`, if specified.
+    fn emit(&self, params: &[&NamedParam], cfg: Option<proc_macro2::TokenStream>) -> proc_macro2::TokenStream {
         let Self {
+            with_params,
+            vis,
             ident,
-            mut generics,
+            generics,
             maybe_self,
             maybe_comma,
-            bindings,
+            bindings: _,
+            maybe_rest,
             return_type,
             body,
+            overlaps: _,
         } = self;
+        let mut generics = generics.clone();
 
-        let args: Vec<_> = bindings.named.into_iter()
-            .sorted_by_cached_key(|field| field.ident.as_ref().unwrap().to_string())
-            .collect();
-        let arg_idents: Vec<_> = args.iter().map(|field| field.ident.as_ref().unwrap()).collect();
-        let arg_names: Vec<_> = arg_idents.iter().map(|ident| ident.to_string()).collect();
-
-        // Generate new generic types for our named fields.
-        let new_generics: Vec<TypeParam> = arg_names.iter()
-            .map(|name| {
-                let ident = format_ident!{"Obstruct_{}", name};
-                parse_quote! {
-                    #ident
-                }
-            })
+        let params: Vec<_> = params.iter()
+            .sorted_by_cached_key(|param| param.name())
             .collect();
-
-        for (arg_name, (arg, generic_type)) in zip(&arg_names, zip(&args, &new_generics)) {
-            let field_name = LitStr::new(arg_name.as_str(), arg.ident.as_ref().unwrap().span());
+        let arg_names: Vec<_> = params.iter().map(|param| param.name()).collect();
+
+        // We accept any anonymous struct `Args` that has our named fields, in any order. We
+        // remove them one by one, each removal producing an anonymous struct `Rest0`, `Rest1`…
+        // with the remaining fields. Without `..`, the last removal must leave no field.
+        // These generic types have names that user code can't guess, see `GenericNames`.
+        //
+        // We use `HasArg` and `NoOtherArg` rather than `Has` and `()` for the sake of
+        // error messages, which mention the name of the function and list the named
+        // arguments in `Args`. Without `#[params]`, they also suggest it.
+        let function_name = LitStr::new(&ident.to_string(), ident.span());
+        // Not visible to the body of the function, which may have a named argument `args`.
+        let args_ident = Ident::new("obstruct_args", Span::mixed_site());
+        let fields = params.iter().map(|param| &param.field);
+        let generic_names = GenericNames::new(&quote!{ #ident #generics #(#fields)* #return_type #body }, ident.span());
+        let args_type = generic_names.ident("Args");
+        generics.params.push(parse_quote!(#args_type));
+        let mut unpack = proc_macro2::TokenStream::new();
+        let mut prev = args_type.clone();
+        for (i, (arg_name, param)) in zip(&arg_names, &params).enumerate() {
+            let arg = &param.field;
+            let arg_ident = arg.ident.as_ref().unwrap();
+            let field_name = arg_name.lit();
             let field_type = &arg.ty;
-            let constrained = parse_quote!{
-                #generic_type: obstruct::Field<#field_type, NAME=#field_name>
-            };
-            generics.params.push(constrained)
+            let index = generic_names.ident(format_args!("Index{i}"));
+            let rest = generic_names.ident(format_args!("Rest{i}"));
+            generics.params.push(parse_quote!(#index));
+            generics.params.push(parse_quote!(#rest));
+            // Errors point at the declaration of the named argument.
+            let obstruct = krate::path_at(arg_ident.span());
+            generics.make_where_clause().predicates.push(parse_quote_spanned!{arg_ident.span()=>
+                #prev: #obstruct::HasArg<#function_name, #with_params, #args_type, #field_name, #field_type, #index, #rest>
+            });
+            // Other attributes, e.g. `#[allow(unused)]`, apply to the variable.
+            let attrs = arg.attrs.iter().filter(|attr| !cfg::is_cfg(attr) && !param_attrs::is_param_attr(attr));
+            unpack.append_all(quote!{
+                #(#attrs)*
+                let (#arg_ident, #args_ident) = #OBSTRUCT::HasArg::<#function_name, #with_params, #args_type, #field_name, #field_type, #index, #rest>::remove_arg(#args_ident);
+            });
+            prev = rest;
+        }
+        if maybe_rest.is_none() {
+            generics.make_where_clause().predicates.push(parse_quote!{
+                #prev: #OBSTRUCT::NoOtherArg<#function_name, #with_params>
+            });
         }
 
-        // Generate constraints for our named fields
-
-        let result = quote! {
-            // Outer function
-            fn #ident #generics(#maybe_self #maybe_comma args: (#( #new_generics ),*) ) #return_type {
-                use obstruct::Field;
-                let (#(#arg_idents),*) = args;
-                #(
-                    let #arg_idents = #arg_idents.take();
-                )*
-
-                // FIXME: Self?
+        // With `#[params]`, let `call!` know about our named arguments, their default values
+        // and attributes, see `InstructFunctionCall::transform`, e.g. `[a, #[alias(c)] b = 9, ..]`.
+        // Otherwise, we don't declare any item besides the function, which may then be
+        // declared wherever a function may be, e.g. in an `impl`.
+        let companion = if *with_params {
+            let mut companion_params: Vec<_> = params.iter().map(|param| {
+                let attrs = param.field.attrs.iter().filter(|attr| param_attrs::is_param_attr(attr));
+                let ident = &param.field.ident;
+                match param.default {
+                    Some(ref default) => quote!{ #(#attrs)* #ident = #default },
+                    None => quote!{ #(#attrs)* #ident },
+                }
+            }).collect();
+            companion_params.extend(maybe_rest.map(|rest| quote!{ #rest }));
+            // `call!` passes deprecated named arguments through these functions, for the
+            // compiler to warn.
+            let deprecated = params.iter().filter_map(|param| {
+                let attr = param.field.attrs.iter().find(|attr| param_attrs::is_deprecated(attr))?;
+                let ident = &param.field.ident;
+                Some(quote!{
+                    #attr
+                    pub fn #ident<T>(value: T) -> T {
+                        value
+                    }
+                })
+            });
+            let companion_ident = param_attrs::companion(ident);
+            let body = quote!{
+                ($($call:tt)*) => {
+                    #OBSTRUCT::__private::call_with_params!{ crate = #OBSTRUCT; [#(#companion_params),*] $($call)* }
+                };
+            };
+            // A `macro_rules!` macro may be re-exported at most to the crate, unless it is
+            // exported from the root of the crate, where its name must not collide with
+            // that of other functions.
+            let companion_macro = if matches!(vis, Visibility::Public(_)) {
+                let exported = generic_names.ident("Params");
+                quote!{
+                    #[doc(hidden)]
+                    #[macro_export]
+                    macro_rules! #exported { #body }
+                    pub use #exported as obstruct_params;
+                }
+            } else {
+                quote!{
+                    macro_rules! obstruct_params { #body }
+                    pub(crate) use obstruct_params;
+                }
+            };
+            quote!{
+                #cfg
+                #[doc(hidden)]
+                #[allow(non_snake_case, unused_imports, unused_macros, dead_code)]
+                #vis mod #companion_ident {
+                    #companion_macro
+                    #(#deprecated)*
+                }
+            }
+        } else {
+            quote!{}
+        };
 
-                // Inner function
-                fn #ident( #(#args),* ) #return_type #body
+        let where_clause = &generics.where_clause;
+        let stmts = &body.stmts;
+        quote! {
+            #companion
 
-                // And inner function call
-                #ident(#(#arg_idents),*)
+            #cfg
+            #vis fn #ident #generics(#maybe_self #maybe_comma #args_ident: #args_type) #return_type #where_clause {
+                #unpack
+                #(#stmts)*
             }
-        };
-        eprintln!("YORIC: Generated code {}", result);
-        result.into()
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,198 @@
+//! Opt-in dump of the code generated by our macros, to help debug them.
+//!
+//! If environment variable `OBSTRUCT_INSPECT` is set when compiling a crate, every
+//! expansion is written, along with the location of the macro call, to
+//! `target/<profile>/obstruct-inspect/<crate>-<kind>-<hash>.rs`, next to the artifacts of the
+//! crate, e.g. `target/debug/obstruct-inspect/foo-lib-0123456789abcdef.rs`. The kind and hash
+//! tell apart the targets of a crate, e.g. its library and its tests, which are compiled
+//! separately.
+//!
+//! Paths to `obstruct` are written as the caller would, e.g. `::obstruct::Named`, rather than
+//! as the `$crate` passed by the macros exported by `obstruct`, which isn't valid Rust.
+//!
+//! If the compiler wasn't told where to write artifacts, e.g. in an IDE, and the crate has no
+//! `OUT_DIR`, nothing is written.
+//!
+//! As cargo doesn't know that our macros read `OBSTRUCT_INSPECT`, changing it doesn't
+//! cause a rebuild: touch the sources of the crate, or `cargo clean -p <crate>`.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use proc_macro::{Span, TokenStream};
+use proc_macro2::{Delimiter, Spacing, TokenTree};
+
+const INDENT: &str = "    ";
+
+/// Whether we have already written to the dump of the crate being compiled.
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Record the expansion of `macro_name`, called at `location`, if `OBSTRUCT_INSPECT` is set,
+/// then return it.
+pub fn expansion(macro_name: &str, location: Span, tokens: TokenStream) -> TokenStream {
+    if std::env::var_os("OBSTRUCT_INSPECT").is_some() {
+        // Debugging aid: never fail the build.
+        let _ = dump(macro_name, location, &tokens);
+    }
+    tokens
+}
+
+fn dump(macro_name: &str, location: Span, tokens: &TokenStream) -> std::io::Result<()> {
+    let Some(dir) = dump_dir() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir)?;
+    let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_else(|_| "unknown".to_string());
+    // Without `--crate-type`, rustc compiles a binary.
+    let kind = if rustc_arg("--test").is_some() {
+        "test".to_string()
+    } else {
+        rustc_arg("--crate-type").flatten().unwrap_or_else(|| "bin".to_string())
+    };
+    // Cargo passes e.g. `-C extra-filename=-0123456789abcdef`, dash included.
+    let hash = rustc_codegen_option("extra-filename").unwrap_or_default();
+
+    // Start afresh with each compilation of the crate.
+    let append = STARTED.swap(true, Ordering::Relaxed);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(dir.join(format!("{crate_name}-{kind}{hash}.rs")))?;
+
+    let mut out = format!("// {}:{}:{}: {macro_name}!\n", location.file(), location.line(), location.column());
+    pretty(proc_macro2::TokenStream::from(tokens.clone()), 0, &crate_path(&crate_name), &mut out);
+    out.push('\n');
+    file.write_all(out.as_bytes())
+}
+
+/// The directory of the dumps: `obstruct-inspect` next to the directory of the artifacts of
+/// the crate being compiled, e.g. `target/debug/obstruct-inspect` for `target/debug/deps`, or
+/// in `OUT_DIR`, if any.
+///
+/// Unlike looking for the target directory from `CARGO_MANIFEST_DIR`, this honors
+/// `build.target-dir`, and never writes to the sources of dependencies, e.g. in
+/// `~/.cargo/registry`.
+fn dump_dir() -> Option<PathBuf> {
+    if let Some(Some(out_dir)) = rustc_arg("--out-dir") {
+        let out_dir = PathBuf::from(out_dir);
+        let parent = out_dir.parent().unwrap_or(&out_dir);
+        return Some(parent.join("obstruct-inspect"));
+    }
+    std::env::var_os("OUT_DIR").map(|dir| PathBuf::from(dir).join("obstruct-inspect"))
+}
+
+/// The argument of the compiler `name`, if specified: `Some(Some(value))` for `name value`
+/// or `name=value`, or `Some(None)` for a flag, such as `--test`.
+///
+/// Macros are expanded within the compiler, so these are the arguments passed by cargo.
+fn rustc_arg(name: &str) -> Option<Option<String>> {
+    let mut args = std::env::args().peekable();
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return Some(args.next_if(|value| !value.starts_with('-')));
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(Some(value.to_string()));
+        }
+    }
+    None
+}
+
+/// The path to `obstruct` from the crate being compiled, which `$crate` resolves to.
+///
+/// This is `crate` within `obstruct` itself, otherwise the name under which cargo passes
+/// `obstruct` with `--extern`, which may differ if the dependency is renamed. If `obstruct`
+/// is only reached through another crate that re-exports it, there is no such path, so we
+/// fall back to `::obstruct`.
+fn crate_path(crate_name: &str) -> String {
+    if crate_name == "obstruct" {
+        return "crate".to_string();
+    }
+    let mut args = std::env::args();
+    while let Some(arg) = args.next() {
+        let dependency = match arg.strip_prefix("--extern") {
+            Some("") => args.next(),
+            Some(dependency) => dependency.strip_prefix('=').map(str::to_string),
+            None => continue,
+        };
+        let Some((name, path)) = dependency.as_deref().and_then(|dependency| dependency.split_once('=')) else {
+            continue;
+        };
+        let file_name = PathBuf::from(path).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        if file_name.starts_with("libobstruct-") || file_name.starts_with("libobstruct.") {
+            return format!("::{name}");
+        }
+    }
+    "::obstruct".to_string()
+}
+
+/// The value of codegen option `name` of the compiler, e.g. `-C extra-filename=-0123`.
+fn rustc_codegen_option(name: &str) -> Option<String> {
+    let mut args = std::env::args();
+    while let Some(arg) = args.next() {
+        let option = match arg.strip_prefix("-C") {
+            Some("") => args.next()?,
+            Some(option) => option.to_string(),
+            None => continue,
+        };
+        if let Some(value) = option.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Append `tokens` to `out`, one statement per line, indenting the contents of braces, and
+/// writing `$crate` as `crate_path`.
+///
+/// This is only meant to be readable, not to match `rustfmt`.
+fn pretty(tokens: proc_macro2::TokenStream, depth: usize, crate_path: &str, out: &mut String) {
+    let mut line = String::new();
+    // Whether the previous token is a punctuation joined to the next one, e.g. the first `:` of `::`.
+    let mut joint = false;
+    let flush = |line: &mut String, out: &mut String| {
+        if !line.is_empty() {
+            let _ = writeln!(out, "{}{}", INDENT.repeat(depth), line);
+            line.clear();
+        }
+    };
+    for tree in tokens {
+        match tree {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push('{');
+                flush(&mut line, out);
+                pretty(group.stream(), depth + 1, crate_path, out);
+                line.push('}');
+                joint = false;
+            }
+            TokenTree::Punct(punct) if punct.as_char() == ';' => {
+                line.push(';');
+                flush(&mut line, out);
+                joint = false;
+            }
+            tree => {
+                if line.ends_with('}') {
+                    flush(&mut line, out);
+                }
+                if !line.is_empty() && !joint {
+                    line.push(' ');
+                }
+                joint = matches!(&tree, TokenTree::Punct(punct) if punct.spacing() == Spacing::Joint);
+                match &tree {
+                    TokenTree::Ident(ident) if ident == "$crate" => line.push_str(crate_path),
+                    // Groups other than braces are printed on one line, `$crate` included.
+                    TokenTree::Group(_) => line.push_str(&tree.to_string().replace("$crate", crate_path)),
+                    _ => line.push_str(&tree.to_string()),
+                }
+            }
+        }
+    }
+    flush(&mut line, out);
+}
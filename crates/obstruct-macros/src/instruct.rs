@@ -1,245 +1,537 @@
 //! Implementation of macros to create an anonymous struct
 
+use std::iter::zip;
+
 use itertools::Itertools;
 use proc_macro::TokenStream;
-use quote::{quote, TokenStreamExt};
-use syn::{parse::Parse, Token, Ident, Expr, braced, LitStr};
+use proc_macro2::{Delimiter, Group, Spacing, Span};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{parse::Parse, Token, Attribute, Expr, ExprPath, Ident, Type, braced, bracketed, parse_quote};
+
+use crate::{cfg, krate::OBSTRUCT, name::FieldName, param_attrs, repr, suggest};
 
-/// A binding `foo: expr`.
+/// A binding `foo: expr`, `foo: { bar: expr, ... }` (a nested anonymous struct) or,
+/// using the field init shorthand, `foo`, optionally preceded by attributes, e.g. `#[cfg(unix)]`.
 #[derive(Debug)]
 struct BasicBind {
-    ident: Ident,
+    attrs: Vec<Attribute>,
+    name: FieldName,
     expr: Expr
 }
+impl BasicBind {
+    fn is_conditional(&self) -> bool {
+        self.attrs.iter().any(cfg::is_cfg)
+    }
+}
 impl Parse for BasicBind {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let ident = input.parse()?;
-        input.parse::<Token![:]>()?;
-        let expr = input.parse()?;
-        Ok(BasicBind { ident, expr })
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name: FieldName = input.parse()?;
+        let maybe_colon: Option<Token![:]> = input.parse()?;
+        let expr = if maybe_colon.is_some() {
+            if is_nested_struct(input) {
+                let braces_content;
+                braced!(braces_content in input);
+                let nested: InstructStruct = braces_content.parse()?;
+                Expr::Verbatim(nested.into_tokens())
+            } else {
+                input.parse()?
+            }
+        } else {
+            // Shorthand `foo` is equivalent to `foo: foo`.
+            let ident = name.to_ident("value")?;
+            parse_quote!(#ident)
+        };
+        Ok(BasicBind { attrs, name, expr })
     }
 }
 
-fn check_for_duplicates(bindings: &[BasicBind]) -> Result<(), syn::Error> {
-    // Check for duplicates.
-    let mut prev: Option<&BasicBind> = None;
-    for binding in bindings {
-        if let Some(prev) = prev {
-            if prev.ident == binding.ident {
-                return Err(syn::Error::new(
-                    binding.ident.span(),
-                    format!("Duplicate field {}", prev.ident)))
-            }
-        }
-        prev = Some(binding);
+/// Determine whether the next tokens are a nested anonymous struct `{ foo: expr, ... }`
+/// rather than a block.
+///
+/// A brace group is a nested anonymous struct if it starts with `foo:` or `foo,`, or
+/// with an index `0:`, possibly after attributes. In particular, `{ foo }` is a block, while `{ foo, }` is a nested
+/// anonymous struct.
+fn is_nested_struct(input: syn::parse::ParseStream) -> bool {
+    let Some((mut content, _, _)) = input.cursor().group(Delimiter::Brace) else {
+        return false;
+    };
+    // Skip attributes, e.g. `#[cfg(unix)]`.
+    while let Some((punct, after_punct)) = content.punct() {
+        let (true, Some((_, _, after_attr))) = (punct.as_char() == '#', after_punct.group(Delimiter::Bracket)) else {
+            break;
+        };
+        content = after_attr;
+    }
+    let after_name = match (content.ident(), content.literal()) {
+        (Some((_, after_ident)), _) => after_ident,
+        (None, Some((_, after_literal))) => after_literal,
+        (None, None) => return false,
+    };
+    match after_name.punct() {
+        Some((punct, _)) if punct.as_char() == ',' => true,
+        Some((punct, _)) if punct.as_char() == ':' => punct.spacing() == Spacing::Alone,
+        _ => false,
     }
-    Ok(())
 }
 
-/// The contents of an anonymous struct creation, e.g. `instruct!{ x: 7, 8: 9 }`.
-pub struct InstructStruct(Vec<BasicBind>);
+/// The contents of an anonymous struct creation, e.g. `instruct!{ x: 7, 8: 9 }` or `instruct!{ x, y: 9 }`,
+/// along with the statements that check that no field is defined twice, see `cfg::check_for_duplicates`.
+pub struct InstructStruct(Vec<BasicBind>, proc_macro2::TokenStream);
 impl Parse for InstructStruct {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let bindings: Vec<BasicBind> = input.parse_terminated(<BasicBind as Parse>::parse, Token![,])?
-            .into_iter()
+        let bindings = input.parse_terminated(<BasicBind as Parse>::parse, Token![,])?;
+        // With `#[cfg]`s, e.g. `#[cfg(unix)] fd: fd` and `#[cfg(windows)] fd: handle`, a name
+        // may appear twice.
+        let overlaps = cfg::check_for_duplicates(bindings.iter().map(|binding| (&binding.name, &binding.attrs[..])), "field")?;
+        let bindings: Vec<BasicBind> = bindings.into_iter()
             // Normalize order.
-            // Any order will do.
-            .sorted_by_cached_key(|assign| assign.ident.to_string())
+            // Any order will do, as long as all our macros agree.
+            .sorted_by(|a, b| a.name.cmp(&b.name))
             .collect();
-        check_for_duplicates(&bindings)?;
 
-        Ok(Self(bindings))
+        Ok(Self(bindings, overlaps))
     }
 }
 
 impl InstructStruct {
     /// Emit the code to represent this anonymous struct.
     ///
-    /// We rewrite `let foo = instruct!{ x: 7, y: 9 };` into
+    /// We rewrite `let foo = instruct!{ y: 9, x: 7 };` into
     ///
     /// ```ignore
-    /// let foo = {
-    ///    use obstruct::Field;
-    ///
-    ///    // One struct per field.
-    ///    // As we don't have type information, these structs are generic.
-    ///    struct x<T>(T);
-    ///    struct y<T>(T);
-    ///
-    ///    // Each struct implements `Field` for its field name.
-    ///    impl<T> Field<T> for x<T> {
-    ///       const NAME: &'static str = "x";
-    ///       fn take(self) -> T {
-    ///         self.0
-    ///       }
-    ///    }
-    ///    impl<T> Field<T> for y<T> {
-    ///       const NAME: &'static str = "y";
-    ///       fn take(self) -> T {
-    ///         self.0
-    ///       }
-    ///    }
-    ///
-    ///    (x(7), y(9))
-    /// };
+    /// let foo = (obstruct::Named::<"x", _>(7), (obstruct::Named::<"y", _>(9), ()));
     /// ```
+    ///
+    /// All fields share the same wrapper type `Named`, which carries the name of the
+    /// field as a type-level string, so we don't need to define any item per field.
     pub fn transform(self) -> TokenStream {
-        // Parse input.
+        self.into_tokens().into()
+    }
+
+    fn into_tokens(self) -> proc_macro2::TokenStream {
+        if self.0.iter().any(|binding| !binding.attrs.is_empty()) {
+            return self.into_tokens_with_attrs();
+        }
         let fields = self.0.into_iter()
-            .map(|BasicBind { ident, expr }| {
-                let field_name = LitStr::new(&ident.to_string(), ident.span());
+            .map(|BasicBind { name, expr, .. }| {
+                let field_name = name.lit();
+                // Struct syntax rather than a call, to avoid linting on `foo: { expr }`.
                 quote!{
-                    {
-                        use obstruct::Field;
-
-                        // Define a local structure representing the field.
-                        #[allow(non_camel_case_types)]
-                        #[derive(Clone)]
-                        struct #ident<T>(T);
-
-                        // Make it an instance of `Field`.
-                        impl<T> Field<T> for #ident<T> {
-                            const NAME: &'static str = #field_name;
-                            fn take(self) -> T {
-                                self.0
-                            }
-                        }
-
-                        impl<'a, T> Field<&'a T> for &'a #ident<T> {
-                            const NAME: &'static str = #field_name;
-                            fn take(self) -> &'a T {
-                                &self.0
-                            }
-                        }
-
-                        impl<'a, T> Field<&'a mut T> for &'a mut #ident<T> {
-                            const NAME: &'static str = #field_name;
-                            fn take(self) -> &'a mut T {
-                                &mut self.0
-                            }
-                        }
-                        // This is the value we're looking for.
-                        #ident(#expr)
-                    }
+                    #OBSTRUCT::Named::<#field_name, _> { 0: #expr }
                 }
             });
 
+        // Turn it into a list.
+        repr::nest(fields)
+    }
 
-        let mut tokens = proc_macro2::TokenStream::new();
-        tokens.append_separated(fields, quote!{,});
-
-        eprintln!("YORIC: {tokens}");
-
-        // Turn it into a tuple.
+    /// Emit the code to represent this anonymous struct, if some fields have attributes.
+    ///
+    /// As attributes such as `#[cfg]` may apply to statements, but not (yet) to expressions,
+    /// we rewrite `instruct!{ y: 9, #[cfg(unix)] x: 7 }` into
+    ///
+    /// ```ignore
+    /// {
+    ///     #[cfg(unix)]
+    ///     let obstruct_field_x = 7;
+    ///     let obstruct_field_y = 9;
+    ///     let obstruct_struct = ();
+    ///     let obstruct_struct = (obstruct::Named::<"y", _>(obstruct_field_y), obstruct_struct);
+    ///     #[cfg(unix)]
+    ///     let obstruct_struct = (obstruct::Named::<"x", _>(obstruct_field_x), obstruct_struct);
+    ///     obstruct_struct
+    /// }
+    /// ```
+    ///
+    /// If `x` is disabled, the result is `(obstruct::Named::<"y", _>(9), ())`, still in
+    /// canonical order. The block starts with the checks that no field is defined twice.
+    fn into_tokens_with_attrs(self) -> proc_macro2::TokenStream {
+        let struct_ident = Ident::new("obstruct_struct", Span::mixed_site());
+        let value_ident = |name: &FieldName| format_ident!("obstruct_field_{}", name, span = Span::mixed_site());
+        let values = self.0.iter().map(|BasicBind { attrs, name, expr }| {
+            let value = value_ident(name);
+            quote!{
+                #(#attrs)*
+                let #value = #expr;
+            }
+        });
+        // Built from the last field.
+        let fields = self.0.iter().rev().map(|BasicBind { attrs, name, .. }| {
+            let value = value_ident(name);
+            let field_name = name.lit();
+            quote!{
+                #(#attrs)*
+                let #struct_ident = (#OBSTRUCT::Named::<#field_name, _> { 0: #value }, #struct_ident);
+            }
+        });
+        let overlaps = &self.1;
         quote!{
-            (#tokens)
-        }.into()
+            {
+                #overlaps
+                #(#values)*
+                let #struct_ident = ();
+                #(#fields)*
+                #struct_ident
+            }
+        }
     }
 }
 
 
 
-/// The contents of an anonymous struct function call, e.g. `call!{ foo, {x: 7, 8: 9} }` or `call!{ foo.bar, { x: 7, y: 9} }` .
+/// The contents of an anonymous struct function call, e.g. `call!{ foo, {x: 7, 8: 9} }`, `call!{ foo.bar, { x: 7, y: 9} }`
+/// or `call!{ foo, { x, y: 9 } }`, optionally preceded by `#[params]`, e.g. `call!{ #[params] foo, { x: 7 } }`.
 ///
 /// Missing features:
 /// - passing regular arguments
 pub struct InstructFunctionCall {
+    /// Whether the callee was declared with `destruct!{ #[params] fn ... }`.
+    with_params: bool,
+
     /// The callee (e.g. `foo` or `foo.bar`).
     callee: Expr,
 
     /// Named arguments
     args: InstructStruct,
+
+    /// Named arguments, as written by the caller, braces included.
+    raw_args: Group,
 }
 impl Parse for InstructFunctionCall {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let with_params = param_attrs::params(&input.call(Attribute::parse_outer)?)?;
+
         // Callee
         let callee: Expr = input.parse()?;
         let _ = input.parse::<Token![,]>()?;
 
         // Arguments
+        let raw_args: Group = input.fork().parse()?;
         let braces_content;
         let _braces = braced!(braces_content in input);
+        let args: InstructStruct = braces_content.parse()?;
 
-        let bindings: Vec<BasicBind> = braces_content.parse_terminated(<BasicBind as Parse>::parse, Token![,])?
-            .into_iter()
-            // Normalize order.
-            // Any order will do.
-            .sorted_by_cached_key(|assign| assign.ident.to_string())
-            .collect();
-
-        // Check for duplicates.
-        check_for_duplicates(&bindings)?;
-
-        Ok(InstructFunctionCall { callee, args: InstructStruct(bindings) })
+        Ok(InstructFunctionCall { with_params, callee, args, raw_args })
     }
 }
 impl InstructFunctionCall {
-    /// Emit the code to represent this anonymous struct.
+    /// Emit the code to represent this function call.
     ///
-    /// We rewrite `let foo = call!{bar,  {x: 7, y: 9 }};` into
+    /// With `#[params]`, the callee `bar` is a function declared with
+    /// `destruct!{ #[params] fn bar(...) ... }`, which also declared a module
+    /// `__obstruct_params_bar` containing macro `obstruct_params!`. We rewrite
+    /// `let foo = call!{#[params] bar, {x: 7, y: 9 }};` into
     ///
     /// ```ignore
-    /// let foo = bar(
-    ///     {
-    ///       // One struct per arg.
-    ///       // As we don't have type information, these structs are generic.
-    ///       struct x<T>(T);
-    ///
-    ///       // Each struct implements `Field` for its field name.
-    ///       impl<T> Field<T> for x<T> {
-    ///          const NAME: &'static str = "x";
-    ///          fn take(self) -> T {
-    ///            self.0
-    ///          }
-    ///       }
-    ///
-    ///       x(7)
-    ///     },
-    ///     {
-    ///       struct y<T>(T);
-    ///       impl<T> Field<T> for y<T> {
-    ///          const NAME: &'static str = "9";
-    ///          fn take(self) -> T {
-    ///            self.0
-    ///          }
-    ///       }
-    ///
-    ///       y(9)
-    ///     }
-    /// );
+    /// let foo = __obstruct_params_bar::obstruct_params!{bar, {x: 7, y: 9 }};
     /// ```
+    ///
+    /// which expands to `obstruct::call_with_params!{ [x, y] bar, {x: 7, y: 9 }}`, so
+    /// that we may check the named arguments against the parameters of `bar` and fill in
+    /// default values.
+    ///
+    /// Otherwise, e.g. for methods, closures or functions declared without `destruct!`,
+    /// see `into_call`.
     pub fn transform(self) -> TokenStream {
-        let fields = self.args.0.into_iter()
-            .map(|BasicBind { ident, expr }| {
-                quote!{
-                    {
-                        use obstruct::Field;
-
-                        // Define a local structure representing the field.
-                        #[allow(non_camel_case_types)]
-                        struct #ident<T>(T);
-
-                        // Make it an instance of `Field`.
-                        impl<T> Field<T> for #ident<T> {
-                            const NAME: &'static str = stringify!(#ident);
-                            fn take(self) -> T {
-                                self.0
-                            }
-                        }
-
-                        // This is the value we're looking for.
-                        #ident(#expr)
-                    }
-                }
-            });
+        if !self.with_params {
+            return self.into_call().into();
+        }
+        let companion = match self.companion() {
+            Ok(companion) => companion,
+            Err(error) => return error.to_compile_error().into(),
+        };
+        let callee = &self.callee;
+        let raw_args = &self.raw_args;
+        quote!{
+            #companion::obstruct_params!{ #callee, #raw_args }
+        }.into()
+    }
 
-        let mut fields_tokens = proc_macro2::TokenStream::new();
-        fields_tokens.append_separated(fields, quote!{,});
+    /// The path to the companion module of the callee, e.g. `__obstruct_params_bar` for `bar`.
+    fn companion(&self) -> syn::Result<syn::Path> {
+        match self.callee {
+            Expr::Path(ExprPath { qself: None, ref path, .. }) => param_attrs::companion_path(path),
+            ref callee => Err(syn::Error::new_spanned(callee, "`#[params]` expects the path to a function declared with `destruct!`")),
+        }
+    }
 
+    /// Emit the code to call the function, without checking named arguments.
+    ///
+    /// We rewrite `let foo = call!{bar,  {x: 7, y: 9 }};` into
+    ///
+    /// ```ignore
+    /// let foo = bar(instruct!{x: 7, y: 9});
+    /// ```
+    fn into_call(self) -> proc_macro2::TokenStream {
         let callee = self.callee;
-        let result = quote!{
-            #callee ((#fields_tokens))
+        // Type errors, e.g. on missing arguments, point at the named arguments rather than
+        // at the macro that emitted the call.
+        let span = self.raw_args.span();
+        let args: proc_macro2::TokenStream = self.args.into_tokens()
+            .into_iter()
+            .map(|mut tree| {
+                tree.set_span(span);
+                tree
+            })
+            .collect();
+        quote_spanned!{span=>
+            #callee (#args)
+        }
+    }
+}
+
+/// A parameter of a function declared with `destruct!`, e.g. `x` or, with a default
+/// value, `x = 7`, optionally preceded by `#[alias(...)]` and `#[deprecated]`.
+struct Param {
+    name: FieldName,
+    aliases: Vec<FieldName>,
+    deprecated: bool,
+    default: Option<Expr>,
+}
+impl Parse for Param {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let aliases = param_attrs::aliases(&attrs)?;
+        let deprecated = attrs.iter().any(param_attrs::is_deprecated);
+        let name = input.parse()?;
+        let default = if input.parse::<Option<Token![=]>>()?.is_some() {
+            Some(input.parse()?)
+        } else {
+            None
         };
-        result.into()
+        Ok(Param { name, aliases, deprecated, default })
+    }
+}
+
+/// The contents of `call_with_params!{ [x, y = 9, ..] foo, { x: 7 } }`, i.e. a call to
+/// a function declared with `destruct!`, along with its parameters, their default
+/// values, aliases and deprecation and, if the function accepts other named arguments, `..`.
+pub struct CallWithParams {
+    params: Vec<Param>,
+    maybe_rest: Option<Token![..]>,
+    call: InstructFunctionCall,
+}
+impl Parse for CallWithParams {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let brackets_content;
+        bracketed!(brackets_content in input);
+        let mut params = vec![];
+        let mut maybe_rest = None;
+        while !brackets_content.is_empty() {
+            if brackets_content.peek(Token![..]) {
+                maybe_rest = Some(brackets_content.parse()?);
+                break;
+            }
+            params.push(brackets_content.parse()?);
+            if brackets_content.is_empty() {
+                break;
+            }
+            brackets_content.parse::<Token![,]>()?;
+        }
+        let call = input.parse()?;
+        Ok(CallWithParams { params, maybe_rest, call })
+    }
+}
+impl CallWithParams {
+    /// Check the named arguments, fill in default values, then emit the call as
+    /// `InstructFunctionCall::into_call`.
+    ///
+    /// Unknown named arguments (unless the function accepts them with `..`) and missing
+    /// named arguments are reported here, with a suggestion for misspelled ones, rather
+    /// than by the type checker.
+    ///
+    /// Named arguments passed by an alias are renamed. Deprecated named arguments `x` are
+    /// passed through function `__obstruct_params_foo::x`, declared by `destruct!` with
+    /// `#[deprecated]`, so that the compiler warns the caller.
+    pub fn transform(self) -> TokenStream {
+        let CallWithParams { params, maybe_rest, mut call } = self;
+        let companion = match call.companion() {
+            Ok(companion) => companion,
+            Err(error) => return error.to_compile_error().into(),
+        };
+        let function = &call.callee;
+        for bind in &mut call.args.0 {
+            let Some(param) = params.iter().find(|param| param.name == bind.name || param.aliases.contains(&bind.name)) else {
+                continue;
+            };
+            bind.name = param.name.located_at(bind.name.span());
+            if param.deprecated {
+                let name = &bind.name;
+                let expr = &bind.expr;
+                bind.expr = Expr::Verbatim(quote_spanned!{name.span()=> #companion::#name(#expr) });
+            }
+        }
+        // An argument may have been passed both by its name and by an alias.
+        match cfg::check_for_duplicates(call.args.0.iter().map(|bind| (&bind.name, &bind.attrs[..])), "named argument") {
+            Ok(overlaps) => call.args.1 = overlaps,
+            Err(error) => {
+                let errors = error.to_compile_error();
+                return quote!{ { #errors } }.into();
+            }
+        }
+        let param_names: Vec<String> = params.iter().map(|param| param.name.to_string()).collect();
+        let arg_names: Vec<String> = call.args.0.iter().map(|bind| bind.name.to_string()).collect();
+        let mut missing: Vec<&str> = param_names.iter()
+            .zip(&params)
+            .filter(|(name, param)| param.default.is_none() && !arg_names.contains(name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+
+        let mut errors: Option<syn::Error> = None;
+        let mut report = |error: syn::Error| match errors {
+            None => errors = Some(error),
+            Some(ref mut errors) => errors.combine(error),
+        };
+        for (arg, bind) in zip(&arg_names, &call.args.0) {
+            // With `#[cfg]`, the argument may not exist. If it does, the type checker reports
+            // unknown arguments. With `..`, other arguments are accepted, so they may be
+            // intentional rather than misspelled.
+            if param_names.contains(arg) || bind.is_conditional() || maybe_rest.is_some() {
+                continue;
+            }
+            let unused = param_names.iter()
+                .filter(|param| !arg_names.contains(param))
+                .map(String::as_str);
+            if let Some(suggestion) = suggest::closest(arg, unused) {
+                report(syn::Error::new(
+                    bind.name.span(),
+                    format!("unknown named argument `{arg}`, did you mean `{suggestion}`?")));
+                // Don't report `suggestion` as missing, too.
+                missing.retain(|name| *name != suggestion);
+            } else {
+                report(syn::Error::new(
+                    bind.name.span(),
+                    format!("unknown named argument `{arg}` for `{}`", quote!{ #function })));
+            }
+        }
+        if !missing.is_empty() {
+            let names = missing.iter().map(|name| format!("`{name}`")).join(", ");
+            let plural = if missing.len() == 1 { "" } else { "s" };
+            report(syn::Error::new(
+                call.raw_args.span(),
+                format!("missing named argument{plural} {names} for `{}`", quote!{ #function })));
+        }
+        if let Some(errors) = errors {
+            // A block, as there may be several errors.
+            let errors = errors.to_compile_error();
+            return quote!{ { #errors } }.into();
+        }
+
+        // Fill in default values, including for arguments that may be disabled by `#[cfg]`.
+        for param in params {
+            let Some(expr) = param.default else {
+                continue;
+            };
+            let attrs = match call.args.0.iter().find(|bind| bind.name == param.name) {
+                None => vec![],
+                Some(bind) => match cfg::predicate(&bind.attrs) {
+                    Ok(Some(predicate)) => vec![parse_quote!{ #[cfg(not(#predicate))] }],
+                    Ok(None) => continue,
+                    Err(error) => return error.to_compile_error().into(),
+                },
+            };
+            call.args.0.push(BasicBind { attrs, name: param.name, expr });
+        }
+        call.args.0.sort_by(|a, b| a.name.cmp(&b.name));
+        call.into_call().into()
+    }
+}
+
+
+/// A field declaration in an anonymous struct type, e.g. `foo: Type` or `foo: { bar: Type, ... }`,
+/// optionally preceded by `#[cfg]` attributes.
+struct TypeBind {
+    attrs: Vec<Attribute>,
+    name: FieldName,
+    ty: Type,
+}
+impl Parse for TypeBind {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        if let Some(attr) = attrs.iter().find(|attr| !cfg::is_cfg(attr)) {
+            return Err(syn::Error::new_spanned(attr, "unsupported attribute, expected `#[cfg]`"));
+        }
+        let name: FieldName = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = if input.peek(syn::token::Brace) {
+            let braces_content;
+            braced!(braces_content in input);
+            let nested: InstructType = braces_content.parse()?;
+            Type::Verbatim(nested.into_tokens())
+        } else {
+            input.parse()?
+        };
+        Ok(TypeBind { attrs, name, ty })
+    }
+}
+
+/// The contents of an anonymous struct type, e.g. `Struct!{ x: u32, y: { z: f64 } }`, along
+/// with the statements that check that no field is declared twice, as in `InstructStruct`.
+pub struct InstructType(Vec<TypeBind>, proc_macro2::TokenStream);
+impl Parse for InstructType {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let bindings = input.parse_terminated(<TypeBind as Parse>::parse, Token![,])?;
+        let overlaps = cfg::check_for_duplicates(bindings.iter().map(|binding| (&binding.name, &binding.attrs[..])), "field")?;
+        let bindings: Vec<TypeBind> = bindings.into_iter()
+            // Normalize order, as in `InstructStruct`.
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .collect();
+
+        Ok(Self(bindings, overlaps))
+    }
+}
+impl InstructType {
+    /// Emit the code to represent this anonymous struct type.
+    ///
+    /// We rewrite `Struct!{ y: f64, x: u32 }` into
+    ///
+    /// ```ignore
+    /// (obstruct::Named<"x", u32>, (obstruct::Named<"y", f64>, ()))
+    /// ```
+    ///
+    /// As types can't have attributes, a field with `#[cfg]`s, e.g. `#[cfg(unix)] x: u32`, is
+    /// selected by the value of `cfg!`, as in
+    ///
+    /// ```ignore
+    /// <obstruct::__private::Cfg<{ cfg!(all(unix)) }, obstruct::Named<"x", u32>, (obstruct::Named<"y", f64>, ())> as obstruct::__private::Resolve>::Output
+    /// ```
+    ///
+    /// so the type of a disabled field must still exist. The checks that no field is declared
+    /// twice are statements in the value of a disabled `Cfg`, as in
+    ///
+    /// ```ignore
+    /// <obstruct::__private::Cfg<{ #[cfg(all(all(unix), all(target_os = "linux")))] compile_error!(...); false }, (), ...> as obstruct::__private::Resolve>::Output
+    /// ```
+    pub fn transform(self) -> TokenStream {
+        self.into_tokens().into()
+    }
+
+    fn into_tokens(self) -> proc_macro2::TokenStream {
+        let overlaps = self.1;
+        // Built from the last field.
+        let fields = self.0.into_iter()
+            .rev()
+            .try_fold(quote!{ () }, |rest, TypeBind { attrs, name, ty }| {
+                let field_name = name.lit();
+                let field = quote!{ #OBSTRUCT::Named<#field_name, #ty> };
+                Ok(match cfg::predicate(&attrs)? {
+                    None => quote!{ (#field, #rest) },
+                    Some(predicate) => quote!{
+                        <#OBSTRUCT::__private::Cfg<{ ::core::cfg!(#predicate) }, #field, #rest> as #OBSTRUCT::__private::Resolve>::Output
+                    },
+                })
+            });
+        // As in `Struct!`, report errors as the length of an array type.
+        let fields = fields.unwrap_or_else(|error: syn::Error| {
+            let errors = error.to_compile_error();
+            quote!{ [(); const { #errors 0 }] }
+        });
+        if overlaps.is_empty() {
+            return fields;
+        }
+        quote!{
+            <#OBSTRUCT::__private::Cfg<{ #overlaps false }, (), #fields> as #OBSTRUCT::__private::Resolve>::Output
+        }
     }
 }
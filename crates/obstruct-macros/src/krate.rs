@@ -0,0 +1,93 @@
+//! The path to the `obstruct` crate in generated code.
+//!
+//! By default, generated code refers to `obstruct`. The macros exported by `obstruct`
+//! pass `crate = $crate;` before their input, so that they work even if `obstruct` is
+//! renamed or re-exported by another crate. `#[derive(Obstruct)]` accepts
+//! `#[obstruct(crate = path)]` for the same purpose.
+
+use std::cell::RefCell;
+
+use proc_macro::{Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+
+thread_local! {
+    /// The path to `obstruct` for the expansion in progress, if not `obstruct`.
+    ///
+    /// Tokens may not outlive the expansion that created them, so this is reset to `None`
+    /// after each expansion.
+    static PATH: RefCell<Option<proc_macro2::TokenStream>> = const { RefCell::new(None) };
+}
+
+/// Interpolated as the path to `obstruct`, e.g. `quote!{ #OBSTRUCT::Named }`.
+pub struct Obstruct;
+impl ToTokens for Obstruct {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        PATH.with(|path| match &*path.borrow() {
+            Some(path) => tokens.extend(path.clone()),
+            None => tokens.extend(quote!{ obstruct }),
+        });
+    }
+}
+pub const OBSTRUCT: Obstruct = Obstruct;
+
+/// The path to `obstruct`, located at `span`.
+///
+/// Within `quote_spanned!`, use this rather than `OBSTRUCT`, whose tokens keep their own
+/// location, so that errors point at `span`.
+pub fn path_at(span: proc_macro2::Span) -> proc_macro2::TokenStream {
+    PATH.with(|path| match &*path.borrow() {
+        // A path such as `$crate` must keep its own hygiene to resolve.
+        Some(path) => path.clone()
+            .into_iter()
+            .map(|mut tree| {
+                tree.set_span(tree.span().located_at(span));
+                tree
+            })
+            .collect(),
+        None => quote_spanned!{span=> obstruct },
+    })
+}
+
+/// Whether `input` starts with the prefix `crate = path;`.
+fn has_path(input: &TokenStream) -> bool {
+    let mut lookahead = input.clone().into_iter();
+    matches!(
+        (lookahead.next(), lookahead.next()),
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Punct(punct))) if ident.to_string() == "crate" && punct.as_char() == '='
+    )
+}
+
+/// The location of the first token of `input` written by the caller of the macro, i.e.
+/// after the optional prefix `crate = path;` and the first `skip` token trees, or the call
+/// site if there is none.
+///
+/// Unlike the call site, which is in the `macro_rules!` wrappers of `obstruct`, this is in
+/// the sources of the caller.
+pub fn location(input: &TokenStream, skip: usize) -> Span {
+    let mut trees = input.clone().into_iter();
+    if has_path(input) {
+        trees.by_ref().find(|tree| matches!(tree, TokenTree::Punct(punct) if punct.as_char() == ';'));
+    }
+    trees.nth(skip).map_or_else(Span::call_site, |tree| tree.span())
+}
+
+/// Expand `input` with `expand`, once we have removed the optional prefix `crate = path;`.
+pub fn expand(input: TokenStream, expand: impl FnOnce(TokenStream) -> TokenStream) -> TokenStream {
+    if !has_path(&input) {
+        return expand(input);
+    }
+    let mut trees = input.into_iter();
+    let path: TokenStream = trees.by_ref()
+        .skip(2)
+        .take_while(|tree| !matches!(tree, TokenTree::Punct(punct) if punct.as_char() == ';'))
+        .collect();
+    with_path(path.into(), || expand(trees.collect()))
+}
+
+/// Run `f` with `path` as the path to `obstruct`.
+pub fn with_path<T>(path: proc_macro2::TokenStream, f: impl FnOnce() -> T) -> T {
+    let previous = PATH.with(|current| current.replace(Some(path)));
+    let result = f();
+    PATH.with(|current| current.replace(previous));
+    result
+}
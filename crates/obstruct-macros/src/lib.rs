@@ -1,10 +1,35 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use syn::parse_macro_input;
+use quote::quote;
+use syn::{parse::Parse, parse_macro_input, DeriveInput};
 
+mod cfg;
+mod inspect;
 mod instruct;
+mod krate;
+mod name;
+mod param_attrs;
 mod destruct;
+mod derive;
+mod repr;
+mod select;
+mod suggest;
+mod update;
+
+/// Parse the input of a macro expanding to an expression, then expand it with `transform`.
+///
+/// As an expression can't be followed by another `compile_error!`, errors are reported
+/// within a block.
+fn expand_expr<T: Parse>(input: TokenStream, transform: impl FnOnce(T) -> TokenStream) -> TokenStream {
+    match syn::parse(input) {
+        Ok(contents) => transform(contents),
+        Err(error) => {
+            let errors = error.to_compile_error();
+            quote!{ { #errors } }.into()
+        }
+    }
+}
 
 /// Expand an expression `instruct!{ x: 7, y: 9 }`
 ///
@@ -12,15 +37,50 @@ mod destruct;
 /// instead of `FooBar`
 #[proc_macro]
 pub fn instruct(input: TokenStream) -> TokenStream {
-    let contents = parse_macro_input!(input as instruct::InstructStruct);
-    contents.transform()
+    inspect::expansion("instruct", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, instruct::InstructStruct::transform)))
+}
+
+/// Expand a type `Struct!{ x: u32, y: f64 }`
+///
+/// This is the type of anonymous structs with fields `x: u32` and `y: f64`, as produced e.g. by `instruct!`
+/// or by deserialization.
+#[proc_macro]
+#[allow(non_snake_case)]
+pub fn Struct(input: TokenStream) -> TokenStream {
+    inspect::expansion("Struct", krate::location(&input, 0), krate::expand(input, |input| {
+        match syn::parse(input) {
+            Ok(contents) => instruct::InstructType::transform(contents),
+            Err(error) => {
+                // As for `expand_expr`, there may be several errors: report them in a
+                // `const` block, as the length of an array type. Unlike `compile_error!` as
+                // a type, which is `()`, this type doesn't cause errors on its uses.
+                let errors = error.to_compile_error();
+                quote!{ [(); const { #errors 0 }] }.into()
+            }
+        }
+    }))
 }
 
 /// Expand an expression `call!{ foo {x: 7, y: 9} }`
+///
+/// With `call!{ #[params] foo {x: 7} }`, `foo` must be a function declared with
+/// `destruct!{ #[params] fn foo(...) ... }`, so that named arguments may be checked, and
+/// default values filled in, during expansion. Otherwise, this is `foo(instruct!{x: 7})`.
 #[proc_macro]
 pub fn call(input: TokenStream) -> TokenStream {
-    let contents = parse_macro_input!(input as instruct::InstructFunctionCall);
-    contents.transform()
+    inspect::expansion("call", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, instruct::InstructFunctionCall::transform)))
+}
+
+/// Expand an expression `call_with_params!{ [x, y = 9] foo, {x: 7} }`
+///
+/// This is `call!{ foo, {x: 7, y: 9} }`, once we know that the parameters of `foo` are `x`
+/// and `y`, with default value `9`. Used by the macros generated by `destruct!` for each
+/// function declared with `#[params]`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn call_with_params(input: TokenStream) -> TokenStream {
+    // Skip the parameters, which were written by the companion macro.
+    inspect::expansion("call_with_params", krate::location(&input, 1), krate::expand(input, |input| expand_expr(input, instruct::CallWithParams::transform)))
 }
 
 
@@ -28,17 +88,78 @@ pub fn call(input: TokenStream) -> TokenStream {
 ///
 /// This pattern is meant to be analogous to `let FooBar {x, y} = foo`, except with an anonymous `struct`
 ///
+/// Fields may be bound `ref` or `mut`, renamed or matched against inner patterns, e.g.
+/// `destruct!{let {ref x, y: (a, b), pos: {z: _}} = foo}`.
+///
 /// # Missing features
 ///
-/// - ref
-/// - mut
 /// - any kind of `let else`.
-/// - inner patterns
-/// - renamings
-/// - `..`
-/// - `_`
-/// - default values
+/// - `..` in `let`: every field must be destructured. Functions accept it.
+/// - default values in `let`. Functions declared with `#[params]` accept them.
 #[proc_macro]
 pub fn destruct(input: TokenStream) -> TokenStream {
-    destruct::Destruct::transform(input)
+    inspect::expansion("destruct", krate::location(&input, 0), krate::expand(input, |input| {
+        destruct::Destruct::transform(input)
+    }))
+}
+
+/// Expand an expression `pick!(foo, {x, y})`
+///
+/// This is an anonymous struct with only fields `x` and `y` of `foo`.
+#[proc_macro]
+pub fn pick(input: TokenStream) -> TokenStream {
+    inspect::expansion("pick", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, select::Select::transform_pick)))
+}
+
+/// Expand an expression `omit!(foo, {x, y})`
+///
+/// This is an anonymous struct with all the fields of `foo` except `x` and `y`.
+#[proc_macro]
+pub fn omit(input: TokenStream) -> TokenStream {
+    inspect::expansion("omit", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, select::Select::transform_omit)))
+}
+
+/// Expand an expression `rename!(foo, {x => a, y => b})`
+///
+/// This is an anonymous struct with the fields of `foo`, except that `x` is renamed to `a` and `y` to `b`.
+#[proc_macro]
+pub fn rename(input: TokenStream) -> TokenStream {
+    inspect::expansion("rename", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, select::Rename::transform)))
+}
+
+/// Expand an expression `map_field!(foo, x => f)`
+///
+/// This is an anonymous struct with the fields of `foo`, except that the value `v` of `x` is replaced with `f(v)`,
+/// possibly of a different type.
+#[proc_macro]
+pub fn map_field(input: TokenStream) -> TokenStream {
+    inspect::expansion("map_field", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, update::MapField::transform)))
+}
+
+/// Expand an expression `set!(foo.x = value)`
+///
+/// This sets field `x` of anonymous struct `foo` to `value`, as `foo.x = value` would for a regular struct.
+#[proc_macro]
+pub fn set(input: TokenStream) -> TokenStream {
+    inspect::expansion("set", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, update::SetField::transform_set)))
+}
+
+/// Expand an expression `replace!(foo.x = value)`
+///
+/// This sets field `x` of anonymous struct `foo` to `value`, returning the previous value.
+#[proc_macro]
+pub fn replace(input: TokenStream) -> TokenStream {
+    inspect::expansion("replace", krate::location(&input, 0), krate::expand(input, |input| expand_expr(input, update::SetField::transform_replace)))
+}
+
+/// Derive conversions between a struct with named fields and anonymous structs.
+///
+/// With `#[derive(Obstruct)] struct Foo { x: u32, y: f64 }`, values of `Foo` may be converted into
+/// `Struct!{ x: u32, y: f64 }` or destructured with `destruct!`, and any anonymous struct with fields
+/// `x: u32` and `y: f64`, including those produced by `instruct!`, may be converted into a `Foo`.
+#[proc_macro_derive(Obstruct, attributes(obstruct))]
+pub fn derive_obstruct(input: TokenStream) -> TokenStream {
+    let location = krate::location(&input, 0);
+    let input = parse_macro_input!(input as DeriveInput);
+    inspect::expansion("derive(Obstruct)", location, derive::DeriveObstruct::new(input).transform())
 }
@@ -0,0 +1,177 @@
+//! Names of fields and named arguments.
+//!
+//! A field may be named by an identifier, including a raw identifier such as `r#type` or a
+//! non-ASCII identifier, or by an index such as `0`, as in tuple structs. At type-level,
+//! a field is named by its canonical name, e.g. `"type"` for `r#type`. Our macros sort
+//! fields by canonical name, so that they all agree on the order of fields.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use proc_macro2::Span;
+use quote::{format_ident, IdentFragment, ToTokens};
+use syn::{ext::IdentExt, parse::Parse, Ident, LitStr, Member};
+
+/// The name of a field or named argument, e.g. `x`, `r#type` or `0`.
+///
+/// Names are compared by canonical name, so `r#type` and `type` are the same name.
+#[derive(Clone, Debug)]
+pub struct FieldName {
+    /// The name, as written by the user.
+    member: Member,
+
+    /// The canonical name, e.g. `type` for `r#type`.
+    canonical: String,
+}
+impl FieldName {
+    pub fn span(&self) -> Span {
+        match self.member {
+            Member::Named(ref ident) => ident.span(),
+            Member::Unnamed(ref index) => index.span,
+        }
+    }
+
+    /// The same name, located at `span`.
+    pub fn located_at(&self, span: Span) -> FieldName {
+        let mut name = self.clone();
+        match name.member {
+            Member::Named(ref mut ident) => ident.set_span(span),
+            Member::Unnamed(ref mut index) => index.span = span,
+        }
+        name
+    }
+
+    /// The name of the field at type-level, e.g. `"type"` for `r#type`.
+    pub fn lit(&self) -> LitStr {
+        LitStr::new(&self.canonical, self.span())
+    }
+
+    /// The name as an identifier, if it isn't an index.
+    pub fn ident(&self) -> Option<&Ident> {
+        match self.member {
+            Member::Named(ref ident) => Some(ident),
+            Member::Unnamed(_) => None,
+        }
+    }
+
+    /// The name as an identifier, e.g. to bind a variable with the field init shorthand.
+    ///
+    /// Fails if the name is an index, as `0` can't be a variable, with an error suggesting
+    /// `0: example`.
+    pub fn to_ident(&self, example: &str) -> syn::Result<Ident> {
+        self.ident().cloned().ok_or_else(|| syn::Error::new(
+            self.span(),
+            format!("field `{self}` can't use the shorthand syntax, write e.g. `{self}: {example}`")))
+    }
+}
+impl From<Ident> for FieldName {
+    fn from(ident: Ident) -> Self {
+        let canonical = ident.unraw().to_string();
+        FieldName { member: Member::Named(ident), canonical }
+    }
+}
+impl From<Member> for FieldName {
+    fn from(member: Member) -> Self {
+        match member {
+            Member::Named(ident) => ident.into(),
+            Member::Unnamed(ref index) => {
+                let canonical = index.index.to_string();
+                FieldName { member, canonical }
+            }
+        }
+    }
+}
+impl Parse for FieldName {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        input.parse::<Member>().map(FieldName::from)
+    }
+}
+impl ToTokens for FieldName {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.member.to_tokens(tokens)
+    }
+}
+/// The canonical name, e.g. for error messages.
+impl Display for FieldName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.canonical)
+    }
+}
+/// The canonical name, e.g. for `format_ident!("obstruct_field_{}", name)`.
+impl IdentFragment for FieldName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.canonical)
+    }
+}
+impl PartialEq for FieldName {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical == other.canonical
+    }
+}
+impl Eq for FieldName {}
+impl PartialOrd for FieldName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// The canonical order of fields.
+impl Ord for FieldName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.canonical.cmp(&other.canonical)
+    }
+}
+
+/// Check that no name appears twice among `names`, e.g. the fields of an anonymous struct,
+/// in the order in which they were written.
+///
+/// Every duplicate is reported, along with the first occurrence of the name. `kind` is the
+/// kind of names we check, e.g. `"field"`.
+pub fn check_for_duplicates<'a>(names: impl IntoIterator<Item = &'a FieldName>, kind: &str) -> syn::Result<()> {
+    // For each name, its first occurrence and whether we have reported it.
+    let mut firsts: BTreeMap<&str, (&FieldName, bool)> = BTreeMap::new();
+    let mut errors: Option<syn::Error> = None;
+    for name in names {
+        let (first, reported) = firsts.entry(&name.canonical).or_insert((name, false));
+        if std::ptr::eq(*first, name) {
+            continue;
+        }
+        let mut error = syn::Error::new(name.span(), format!("duplicate {kind} `{name}`"));
+        if !*reported {
+            error.combine(syn::Error::new(FieldName::span(first), format!("{kind} `{name}` first defined here")));
+            *reported = true;
+        }
+        match errors {
+            None => errors = Some(error),
+            Some(ref mut errors) => errors.combine(error),
+        }
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// Names of the generic types of an item emitted by our macros, e.g. `__Obstruct5F3A…Args`,
+/// and of the macros it exports from the root of the crate.
+///
+/// Unlike variables, generic types resolve at the call site, whatever their span, so they
+/// may collide with the generic types and types of user code. Each name contains a hash of
+/// the item, which user code can't name without changing the hash.
+pub struct GenericNames(String);
+impl GenericNames {
+    /// Names for the generic types of `item`, declared at `location`.
+    ///
+    /// The location tells identical items apart, e.g. functions in distinct modules, whose
+    /// exported macros would otherwise collide at the root of the crate.
+    pub fn new(item: &impl ToTokens, location: Span) -> Self {
+        let mut hasher = DefaultHasher::new();
+        item.to_token_stream().to_string().hash(&mut hasher);
+        let location = location.unwrap();
+        (location.file(), location.line(), location.column()).hash(&mut hasher);
+        GenericNames(format!("{:016X}", hasher.finish()))
+    }
+
+    /// The generic type `suffix`, e.g. `Args` or `Rest3`, or macro `suffix`, e.g. `Params`.
+    pub fn ident(&self, suffix: impl Display) -> Ident {
+        format_ident!("__Obstruct{}{}", self.0, suffix.to_string())
+    }
+}
@@ -0,0 +1,74 @@
+//! Attributes of the named arguments of functions declared with `destruct!`, which are
+//! handled by `call!`.
+//!
+//! - `#[alias(colour)]` lets callers pass named argument `color` as `colour`.
+//! - `#[deprecated]` warns callers that pass the named argument.
+//!
+//! These attributes, as well as default values, require `#[params]` on both the function,
+//! e.g. `destruct!{ #[params] fn foo(...) ... }`, and the call, e.g.
+//! `call!{ #[params] foo, {...} }`. The function then declares a companion module
+//! describing its named arguments, which the call expands.
+
+use quote::format_ident;
+use syn::{punctuated::Punctuated, Attribute, Ident, Path, Token};
+
+use crate::name::FieldName;
+
+pub fn is_alias(attr: &Attribute) -> bool {
+    attr.path().is_ident("alias")
+}
+
+pub fn is_deprecated(attr: &Attribute) -> bool {
+    attr.path().is_ident("deprecated")
+}
+
+/// Whether `attr` is handled by `call!`, rather than applying to the argument itself.
+pub fn is_param_attr(attr: &Attribute) -> bool {
+    is_alias(attr) || is_deprecated(attr)
+}
+
+/// The aliases listed by the `#[alias(...)]` attributes among `attrs`.
+pub fn aliases(attrs: &[Attribute]) -> syn::Result<Vec<FieldName>> {
+    let mut aliases = vec![];
+    for attr in attrs.iter().filter(|attr| is_alias(attr)) {
+        aliases.extend(attr.parse_args_with(Punctuated::<FieldName, Token![,]>::parse_terminated)?);
+    }
+    Ok(aliases)
+}
+
+/// Parse the attributes of a function or of a call, which may only be `#[params]`.
+///
+/// Return whether `#[params]` was specified.
+pub fn params(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut params = false;
+    for attr in attrs {
+        if !attr.path().is_ident("params") {
+            return Err(syn::Error::new_spanned(attr, "unsupported attribute, expected `#[params]`"));
+        }
+        attr.meta.require_path_only()?;
+        params = true;
+    }
+    Ok(params)
+}
+
+/// The companion module of function `ident`, declared with `destruct!{ #[params] fn ident(...) ... }`.
+///
+/// The name is mangled, so as not to collide with other items. Its visibility is that of the
+/// function, so that `call!{ #[params] ... }` may call the function wherever it is visible,
+/// e.g. `shapes::area`, including from other crates.
+pub fn companion(ident: &Ident) -> Ident {
+    format_ident!("__obstruct_params_{}", ident, span = ident.span())
+}
+
+/// The path to the companion module of the function at `path`, e.g. `foo::__obstruct_params_bar`
+/// for `foo::bar`.
+pub fn companion_path(path: &Path) -> syn::Result<Path> {
+    let mut result = path.clone();
+    match result.segments.last_mut() {
+        Some(segment) if segment.arguments.is_none() => {
+            segment.ident = companion(&segment.ident);
+            Ok(result)
+        }
+        _ => Err(syn::Error::new_spanned(path, "`#[params]` expects the path to a function declared with `destruct!`, without generic arguments")),
+    }
+}
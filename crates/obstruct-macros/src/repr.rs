@@ -0,0 +1,20 @@
+//! The representation of anonymous structs.
+//!
+//! An anonymous struct is a list of fields, sorted by name, represented as nested pairs
+//! terminated by `()`, e.g. `(blue, (green, (red, ())))`. Unlike flat tuples, this works
+//! for any number of fields, including with std's implementations of `Clone`, `Debug`, etc.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Nest `items` (expressions, patterns or types) into a list, e.g. `(a, (b, (c, ())))`.
+pub fn nest<I>(items: I) -> TokenStream where I: IntoIterator<Item = TokenStream>, I::IntoIter: DoubleEndedIterator {
+    nest_onto(items, quote!{ () })
+}
+
+/// Nest `items` in front of list `rest`, e.g. `(a, (b, rest))`.
+pub fn nest_onto<I>(items: I, rest: TokenStream) -> TokenStream where I: IntoIterator<Item = TokenStream>, I::IntoIter: DoubleEndedIterator {
+    items.into_iter()
+        .rev()
+        .fold(rest, |rest, item| quote!{ (#item, #rest) })
+}
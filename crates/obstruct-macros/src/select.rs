@@ -0,0 +1,203 @@
+//! Implementation of macros to select or rename some fields of an anonymous struct.
+
+use itertools::Itertools;
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned, format_ident};
+use syn::{parse::Parse, Token, Ident, Expr, braced};
+
+use crate::krate::{self, OBSTRUCT};
+use crate::name::{check_for_duplicates, FieldName};
+use crate::repr;
+
+/// The contents of `pick!(expr, { a, b })` or `omit!(expr, { a, b })`.
+pub struct Select {
+    expr: Expr,
+
+    /// The fields to pick or omit, in canonical order.
+    names: Vec<FieldName>,
+}
+impl Parse for Select {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let expr: Expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let braces_content;
+        braced!(braces_content in input);
+        let names = braces_content.parse_terminated(FieldName::parse, Token![,])?;
+        check_for_duplicates(&names, "field")?;
+        let names: Vec<FieldName> = names.into_iter().sorted().collect();
+
+        Ok(Select { expr, names })
+    }
+}
+/// Emit the code to remove fields `names` from anonymous struct `expr`.
+///
+/// If `keep_values`, the values of fields are bound to `obstruct_field_<field>`, otherwise they
+/// are dropped. The remaining fields are bound to `obstruct_struct`. Error messages list the
+/// fields of `expr`, i.e. of `obstruct_whole`.
+fn remove_fields<'a>(expr: &Expr, names: impl IntoIterator<Item = &'a FieldName>, keep_values: bool) -> proc_macro2::TokenStream {
+    let struct_ident = Ident::new("obstruct_struct", Span::mixed_site());
+    let whole_ident = Ident::new("obstruct_whole", Span::mixed_site());
+    let removals = names.into_iter().map(|name| {
+        let value = if keep_values {
+            format_ident!("obstruct_field_{}", name, span = Span::mixed_site())
+        } else {
+            Ident::new("_", Span::mixed_site())
+        };
+        let field_name = name.lit();
+        let obstruct = krate::path_at(name.span());
+        // Errors, e.g. on unknown fields, point at the field. Internal names are located
+        // within the macro, so the receiver is wrapped in a block located at the field.
+        quote_spanned!{name.span()=>
+            #[allow(unused_braces)]
+            let (#value, #struct_ident) = #obstruct::RemoveField::<#field_name, _, _, _, _>::remove_field(&#whole_ident, { #struct_ident });
+        }
+    });
+    quote!{
+        let #struct_ident = #OBSTRUCT::IntoAnonymous::into_anonymous(#expr);
+        let #whole_ident = #OBSTRUCT::FieldsOf::of(&#struct_ident);
+        #(#removals)*
+    }
+}
+
+impl Select {
+    /// Emit the code for `pick!(expr, { b, a })`, i.e.
+    ///
+    /// ```ignore
+    /// {
+    ///     obstruct::__require_generic_const_exprs!{}
+    ///     let obstruct_struct = obstruct::IntoAnonymous::into_anonymous(expr);
+    ///     let obstruct_whole = obstruct::FieldsOf::of(&obstruct_struct);
+    ///     let (obstruct_field_a, obstruct_struct) = obstruct::RemoveField::<"a", _, _, _, _>::remove_field(obstruct_struct, &obstruct_whole);
+    ///     let (obstruct_field_b, obstruct_struct) = obstruct::RemoveField::<"b", _, _, _, _>::remove_field(obstruct_struct, &obstruct_whole);
+    ///     (obstruct::Named::<"a", _>(obstruct_field_a), (obstruct::Named::<"b", _>(obstruct_field_b), ()))
+    /// }
+    /// ```
+    pub fn transform_pick(self) -> TokenStream {
+        let remove = remove_fields(&self.expr, &self.names, true);
+        let fields = self.names.iter().map(|name| {
+            let value = format_ident!("obstruct_field_{}", name, span = Span::mixed_site());
+            let field_name = name.lit();
+            quote!{ #OBSTRUCT::Named::<#field_name, _>(#value) }
+        });
+        let fields = repr::nest(fields);
+        quote!{
+            {
+                #remove
+                #fields
+            }
+        }.into()
+    }
+
+    /// Emit the code for `omit!(expr, { b, a })`, i.e.
+    ///
+    /// ```ignore
+    /// {
+    ///     obstruct::__require_generic_const_exprs!{}
+    ///     let obstruct_struct = obstruct::IntoAnonymous::into_anonymous(expr);
+    ///     let obstruct_whole = obstruct::FieldsOf::of(&obstruct_struct);
+    ///     let (_, obstruct_struct) = obstruct::RemoveField::<"a", _, _, _, _>::remove_field(obstruct_struct, &obstruct_whole);
+    ///     let (_, obstruct_struct) = obstruct::RemoveField::<"b", _, _, _, _>::remove_field(obstruct_struct, &obstruct_whole);
+    ///     obstruct_struct
+    /// }
+    /// ```
+    pub fn transform_omit(self) -> TokenStream {
+        let remove = remove_fields(&self.expr, &self.names, false);
+        let struct_ident = Ident::new("obstruct_struct", Span::mixed_site());
+        quote!{
+            {
+                #remove
+                #struct_ident
+            }
+        }.into()
+    }
+}
+
+/// A renaming `old => new`.
+struct Renaming {
+    old: FieldName,
+    new: FieldName,
+}
+impl Parse for Renaming {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let old = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let new = input.parse()?;
+        Ok(Renaming { old, new })
+    }
+}
+
+/// The contents of `rename!(expr, { a => b, c => d })`.
+pub struct Rename {
+    expr: Expr,
+
+    /// The renamings, in canonical order of new names.
+    renamings: Vec<Renaming>,
+}
+impl Parse for Rename {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let expr: Expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let braces_content;
+        braced!(braces_content in input);
+        let renamings = braces_content.parse_terminated(Renaming::parse, Token![,])?;
+        let mut errors = [
+            check_for_duplicates(renamings.iter().map(|renaming| &renaming.old), "field"),
+            check_for_duplicates(renamings.iter().map(|renaming| &renaming.new), "new name"),
+        ].into_iter().filter_map(Result::err);
+        if let Some(mut error) = errors.next() {
+            error.extend(errors);
+            return Err(error);
+        }
+        let renamings: Vec<Renaming> = renamings.into_iter()
+            .sorted_by(|a, b| a.new.cmp(&b.new))
+            .collect();
+
+        Ok(Rename { expr, renamings })
+    }
+}
+impl Rename {
+    /// Emit the code for `rename!(expr, { b => c, a => d })`, i.e.
+    ///
+    /// ```ignore
+    /// {
+    ///     obstruct::__require_generic_const_exprs!{}
+    ///     let obstruct_struct = obstruct::IntoAnonymous::into_anonymous(expr);
+    ///     let obstruct_whole = obstruct::FieldsOf::of(&obstruct_struct);
+    ///     let (obstruct_field_b, obstruct_struct) = obstruct::RemoveField::<"b", _, _, _, _>::remove_field(obstruct_struct, &obstruct_whole);
+    ///     let (obstruct_field_a, obstruct_struct) = obstruct::RemoveField::<"a", _, _, _, _>::remove_field(obstruct_struct, &obstruct_whole);
+    ///     let obstruct_struct = obstruct::Insert::<"c", _, _>::insert(obstruct_struct, obstruct_field_b);
+    ///     let obstruct_struct = obstruct::Insert::<"d", _, _>::insert(obstruct_struct, obstruct_field_a);
+    ///     obstruct_struct
+    /// }
+    /// ```
+    ///
+    /// Renamed fields are inserted in canonical order, so that renaming fields of an anonymous
+    /// struct in canonical order produces an anonymous struct in canonical order. New names
+    /// may not collide with the names of other fields.
+    pub fn transform(self) -> TokenStream {
+        let remove = remove_fields(&self.expr, self.renamings.iter().map(|renaming| &renaming.old), true);
+        let struct_ident = Ident::new("obstruct_struct", Span::mixed_site());
+        let inserts = self.renamings.iter().map(|Renaming { old, new }| {
+            let value = format_ident!("obstruct_field_{}", old, span = Span::mixed_site());
+            let field_name = new.lit();
+            let obstruct = krate::path_at(new.span());
+            // As with removals, errors, e.g. on collisions, point at the new name. Once all
+            // fields have been removed, the anonymous struct is `()`.
+            quote_spanned!{new.span()=>
+                #[allow(unused_braces, clippy::unit_arg)]
+                let #struct_ident = #obstruct::Insert::<#field_name, _, _>::insert({ #struct_ident }, #value);
+            }
+        });
+        quote!{
+            {
+                #OBSTRUCT::__require_generic_const_exprs!{}
+                #remove
+                #(#inserts)*
+                #struct_ident
+            }
+        }.into()
+    }
+}
@@ -0,0 +1,111 @@
+//! Implementation of macros to update a field of an anonymous struct.
+
+use proc_macro::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{parse::Parse, Token, Expr, ExprAssign, ExprField};
+
+use crate::krate;
+use crate::name::FieldName;
+
+/// The contents of `map_field!(expr, foo => f)`.
+pub struct MapField {
+    expr: Expr,
+    name: FieldName,
+    f: Expr,
+}
+impl Parse for MapField {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let name = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let f = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(MapField { expr, name, f })
+    }
+}
+impl MapField {
+    /// Emit the code for `map_field!(expr, foo => f)`, i.e.
+    ///
+    /// ```ignore
+    /// obstruct::Has::<"foo", _, _>::map(obstruct::IntoAnonymous::into_anonymous(expr), f)
+    /// ```
+    pub fn transform(self) -> TokenStream {
+        let MapField { expr, name, f } = self;
+        let field_name = name.lit();
+        let obstruct = krate::path_at(name.span());
+        quote_spanned!{name.span()=>
+            #obstruct::Has::<#field_name, _, _>::map(#obstruct::IntoAnonymous::into_anonymous(#expr), #f)
+        }.into()
+    }
+}
+
+/// The contents of `set!(expr.foo = value)` or `replace!(expr.foo = value)`.
+pub struct SetField {
+    /// The anonymous struct, e.g. `expr`. This must be a place expression.
+    expr: Expr,
+
+    /// The field to set, e.g. `foo` or `0`.
+    name: FieldName,
+
+    value: Expr,
+}
+impl Parse for SetField {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let assign: ExprAssign = input.parse()?;
+        let Expr::Field(ExprField { base, member, .. }) = *assign.left else {
+            return Err(syn::Error::new_spanned(assign.left, "Expected `expr.field`"));
+        };
+        Ok(SetField { expr: *base, name: member.into(), value: *assign.right })
+    }
+}
+impl SetField {
+    /// Emit the code to borrow the field mutably, i.e. for `expr.foo`:
+    ///
+    /// ```ignore
+    /// {
+    ///     use obstruct::GetFieldMut as _;
+    ///     (expr).get_field_mut::<"foo", _, _>()
+    /// }
+    /// ```
+    ///
+    /// As with `expr.foo` on a regular struct, method call syntax lets `expr` be an
+    /// anonymous struct or a reference to one.
+    fn get_mut(&self) -> proc_macro2::TokenStream {
+        let expr = &self.expr;
+        let field_name = self.name.lit();
+        let obstruct = krate::path_at(self.name.span());
+        quote_spanned!{self.name.span()=>
+            {
+                use #obstruct::GetFieldMut as _;
+                (#expr).get_field_mut::<#field_name, _, _>()
+            }
+        }
+    }
+
+    /// Emit the code for `set!(expr.foo = value)`, i.e.
+    ///
+    /// ```ignore
+    /// *{ use obstruct::GetFieldMut as _; (expr).get_field_mut::<"foo", _, _>() } = value
+    /// ```
+    pub fn transform_set(self) -> TokenStream {
+        let get_mut = self.get_mut();
+        let value = self.value;
+        quote!{
+            *#get_mut = #value
+        }.into()
+    }
+
+    /// Emit the code for `replace!(expr.foo = value)`, i.e.
+    ///
+    /// ```ignore
+    /// core::mem::replace({ use obstruct::GetFieldMut as _; (expr).get_field_mut::<"foo", _, _>() }, value)
+    /// ```
+    pub fn transform_replace(self) -> TokenStream {
+        let get_mut = self.get_mut();
+        let value = self.value;
+        quote!{
+            ::core::mem::replace(#get_mut, #value)
+        }.into()
+    }
+}
@@ -0,0 +1,22 @@
+//! Fields enabled or disabled by `#[cfg]` in anonymous struct types.
+
+use std::marker::PhantomData;
+
+/// Anonymous struct `Rest`, preceded by field `Field` if `ENABLED`.
+///
+/// Types can't have attributes, so `Struct!{ #[cfg(unix)] fd: i32, path: String }` is
+/// `<Cfg<{ cfg!(unix) }, Named<"fd", i32>, (Named<"path", String>, ())> as Resolve>::Output`.
+pub struct Cfg<const ENABLED: bool, Field, Rest>(PhantomData<(Field, Rest)>);
+
+/// The anonymous struct represented by a `Cfg`.
+pub trait Resolve {
+    type Output;
+}
+
+impl<Field, Rest> Resolve for Cfg<true, Field, Rest> {
+    type Output = (Field, Rest);
+}
+
+impl<Field, Rest> Resolve for Cfg<false, Field, Rest> {
+    type Output = Rest;
+}
@@ -0,0 +1,162 @@
+//! Compile-time checks performed by `destruct!`, with readable error messages.
+
+use crate::{FieldList, FieldNames, FieldsOf, Here, Named, There};
+
+/// An anonymous struct without fields, i.e. `()`.
+///
+/// `destruct!{ let ... }` requires that no field remains once all the fields of the
+/// pattern have been removed.
+pub trait NoOtherField {
+    /// Consume the (empty) anonymous struct.
+    fn no_other_field(self);
+}
+
+impl NoOtherField for () {
+    fn no_other_field(self) {}
+}
+
+impl<A, R> NoOtherField for (A, R) where R: OtherFieldsIn<(A, R)> {
+    fn no_other_field(self) {}
+}
+
+/// The last fields of anonymous struct `Fields`, which has fields that are not destructured.
+///
+/// This is never implemented: it makes `NoOtherField` fail only at the end of `Fields`. If
+/// a field was missing, the fields that remain after it are unknown, and the compiler
+/// doesn't report them as not destructured.
+#[diagnostic::on_unimplemented(
+    message = "anonymous struct has fields that are not destructured",
+    label = "fields not destructured: `{Fields}`",
+    note = "every field of the anonymous struct must appear in the pattern",
+)]
+pub trait OtherFieldsIn<Fields> {}
+
+impl<A, R, Fields> OtherFieldsIn<Fields> for (A, R) where R: OtherFieldsIn<Fields> {}
+
+/// An anonymous struct passed to function `FUNCTION`, with named argument `NAME` of type `T`.
+///
+/// This is `Has`, with an error message that mentions the function. The other named
+/// arguments, `Rest`, are a parameter rather than an associated type, so that a function
+/// only needs trait bounds on its named arguments, which are reported with this error
+/// message. `Args` are all the named arguments passed to the function, which the error
+/// message lists. Unless `PARAMS`, i.e. the function was declared with `#[params]`, the
+/// error message suggests declaring it so.
+pub trait HasArg<const FUNCTION: &'static str, const PARAMS: bool, Args, const NAME: &'static str, T, Index, Rest> {
+    /// Remove named argument `NAME`, returning its value and the other named arguments.
+    fn remove_arg(self) -> (T, Rest);
+}
+
+impl<const FUNCTION: &'static str, Args, ArgNames, const NAME: &'static str, T, Index, Rest, S> HasArg<FUNCTION, true, Args, NAME, T, Index, Rest> for S
+    where Args: FieldNames<Names = ArgNames>,
+          ArgNames: FieldList,
+          FieldsOf<Args>: ArgIn<FUNCTION, { ArgNames::FIELD_LIST }, S, NAME, T, Index, Rest>,
+{
+    fn remove_arg(self) -> (T, Rest) {
+        <FieldsOf<Args> as ArgIn<FUNCTION, { ArgNames::FIELD_LIST }, S, NAME, T, Index, Rest>>::remove_arg_in(self)
+    }
+}
+
+impl<const FUNCTION: &'static str, Args, ArgNames, const NAME: &'static str, T, Index, Rest, S> HasArg<FUNCTION, false, Args, NAME, T, Index, Rest> for S
+    where Args: FieldNames<Names = ArgNames>,
+          ArgNames: FieldList,
+          FieldsOf<Args>: PlainArgIn<FUNCTION, { ArgNames::FIELD_LIST }, S, NAME, T, Index, Rest>,
+{
+    fn remove_arg(self) -> (T, Rest) {
+        <FieldsOf<Args> as PlainArgIn<FUNCTION, { ArgNames::FIELD_LIST }, S, NAME, T, Index, Rest>>::remove_arg_in(self)
+    }
+}
+
+/// The lookup of named argument `NAME` of type `T` in `Fields`, the last of named arguments
+/// `ARGS` passed to function `FUNCTION`, leaving other named arguments `Rest`.
+///
+/// This is how `HasArg` is implemented for functions declared with `#[params]`, as
+/// `FieldIn` implements `RemoveField`, for `FieldsOf<Args>`, where `Args` are the named
+/// arguments.
+#[diagnostic::on_unimplemented(
+    message = "missing named argument {NAME} for {FUNCTION}",
+    label = "expected named argument {NAME} of type `{T}`",
+    note = "the available named arguments are {ARGS}",
+)]
+pub trait ArgIn<const FUNCTION: &'static str, const ARGS: &'static str, Fields, const NAME: &'static str, T, Index, Rest> {
+    /// Remove named argument `NAME` from `fields`, returning its value and the other named
+    /// arguments.
+    fn remove_arg_in(fields: Fields) -> (T, Rest);
+}
+
+impl<Args, const FUNCTION: &'static str, const ARGS: &'static str, const N: &'static str, T, R> ArgIn<FUNCTION, ARGS, (Named<N, T>, R), N, T, Here, R> for FieldsOf<Args> {
+    fn remove_arg_in(fields: (Named<N, T>, R)) -> (T, R) {
+        (fields.0.0, fields.1)
+    }
+}
+
+impl<Args, const FUNCTION: &'static str, const ARGS: &'static str, const N: &'static str, T, I, A, R, RR> ArgIn<FUNCTION, ARGS, (A, R), N, T, There<I>, (A, RR)> for FieldsOf<Args>
+    where FieldsOf<Args>: ArgIn<FUNCTION, ARGS, R, N, T, I, RR>,
+{
+    fn remove_arg_in(fields: (A, R)) -> (T, (A, RR)) {
+        let (value, rest) = <FieldsOf<Args> as ArgIn<FUNCTION, ARGS, R, N, T, I, RR>>::remove_arg_in(fields.1);
+        (value, (fields.0, rest))
+    }
+}
+
+/// `ArgIn`, for functions declared without `#[params]`, whose error message suggests it.
+#[diagnostic::on_unimplemented(
+    message = "missing named argument {NAME} for {FUNCTION}",
+    label = "expected named argument {NAME} of type `{T}`",
+    note = "the available named arguments are {ARGS}",
+    note = "for suggestions on misspelled named arguments, declare {FUNCTION} with `#[params]` and call it with `call!{{ #[params] ... }}`",
+)]
+pub trait PlainArgIn<const FUNCTION: &'static str, const ARGS: &'static str, Fields, const NAME: &'static str, T, Index, Rest> {
+    /// Remove named argument `NAME` from `fields`, returning its value and the other named
+    /// arguments.
+    fn remove_arg_in(fields: Fields) -> (T, Rest);
+}
+
+impl<Args, const FUNCTION: &'static str, const ARGS: &'static str, const N: &'static str, T, R> PlainArgIn<FUNCTION, ARGS, (Named<N, T>, R), N, T, Here, R> for FieldsOf<Args> {
+    fn remove_arg_in(fields: (Named<N, T>, R)) -> (T, R) {
+        (fields.0.0, fields.1)
+    }
+}
+
+impl<Args, const FUNCTION: &'static str, const ARGS: &'static str, const N: &'static str, T, I, A, R, RR> PlainArgIn<FUNCTION, ARGS, (A, R), N, T, There<I>, (A, RR)> for FieldsOf<Args>
+    where FieldsOf<Args>: PlainArgIn<FUNCTION, ARGS, R, N, T, I, RR>,
+{
+    fn remove_arg_in(fields: (A, R)) -> (T, (A, RR)) {
+        let (value, rest) = <FieldsOf<Args> as PlainArgIn<FUNCTION, ARGS, R, N, T, I, RR>>::remove_arg_in(fields.1);
+        (value, (fields.0, rest))
+    }
+}
+
+/// The named arguments left once function `FUNCTION` has removed all of its own, i.e. `()`.
+///
+/// As with `HasArg`, unless `PARAMS`, the error message suggests `#[params]`.
+pub trait NoOtherArg<const FUNCTION: &'static str, const PARAMS: bool> {}
+
+impl<const FUNCTION: &'static str, const PARAMS: bool> NoOtherArg<FUNCTION, PARAMS> for () {}
+
+impl<const FUNCTION: &'static str, A, R> NoOtherArg<FUNCTION, true> for (A, R) where R: OtherArgsIn<FUNCTION, (A, R)> {}
+
+impl<const FUNCTION: &'static str, A, R> NoOtherArg<FUNCTION, false> for (A, R) where R: PlainOtherArgsIn<FUNCTION, (A, R)> {}
+
+/// The last of named arguments `Args`, which function `FUNCTION`, declared with `#[params]`,
+/// doesn't expect.
+///
+/// As `OtherFieldsIn`, this is never implemented.
+#[diagnostic::on_unimplemented(
+    message = "too many named arguments for {FUNCTION}",
+    label = "unexpected named arguments `{Args}`",
+    note = "to ignore other named arguments, end the declaration of {FUNCTION} with `..`",
+)]
+pub trait OtherArgsIn<const FUNCTION: &'static str, Args> {}
+
+impl<const FUNCTION: &'static str, A, R, Args> OtherArgsIn<FUNCTION, Args> for (A, R) where R: OtherArgsIn<FUNCTION, Args> {}
+
+/// `OtherArgsIn`, for functions declared without `#[params]`, whose error message suggests it.
+#[diagnostic::on_unimplemented(
+    message = "too many named arguments for {FUNCTION}",
+    label = "unexpected named arguments `{Args}`",
+    note = "to ignore other named arguments, end the declaration of {FUNCTION} with `..`",
+    note = "for suggestions on misspelled named arguments, declare {FUNCTION} with `#[params]` and call it with `call!{{ #[params] ... }}`",
+)]
+pub trait PlainOtherArgsIn<const FUNCTION: &'static str, Args> {}
+
+impl<const FUNCTION: &'static str, A, R, Args> PlainOtherArgsIn<FUNCTION, Args> for (A, R) where R: PlainOtherArgsIn<FUNCTION, Args> {}
@@ -0,0 +1,170 @@
+//! Conversions between anonymous structs and other types.
+
+use crate::{Has, Named, OwnedField};
+
+/// A value that may be converted into an anonymous struct.
+///
+/// This is implemented by anonymous structs themselves and by structs with
+/// `#[derive(Obstruct)]`. This is what lets `destruct!` destructure the latter.
+pub trait IntoAnonymous {
+    /// The anonymous struct.
+    type Anonymous;
+    fn into_anonymous(self) -> Self::Anonymous;
+}
+
+/// An anonymous struct that may be converted into a tuple of its values.
+pub trait IntoTuple {
+    /// The values of the fields, in canonical (alphabetical) order.
+    type Tuple;
+
+    /// Convert into a tuple of values, in canonical (alphabetical) order of field names.
+    ///
+    /// For instance, `instruct!{ red: 0, green: 1.0, blue: 2 }.into_tuple()` is `(2, 1.0, 0)`.
+    fn into_tuple(self) -> Self::Tuple;
+}
+
+/// An anonymous struct that may be built from a tuple of its values.
+///
+/// This is implemented by anonymous struct types that may be named, i.e. `Struct!{ ... }`.
+pub trait FromTuple: IntoTuple {
+    /// Build from a tuple of values, in canonical (alphabetical) order of field names.
+    ///
+    /// For instance, `<Struct!{ red: u8, blue: u8 }>::from_tuple((2, 0))` has `blue: 2` and `red: 0`.
+    fn from_tuple(tuple: Self::Tuple) -> Self;
+}
+
+/// An anonymous struct whose fields may be borrowed.
+///
+/// This is what lets `destruct!` destructure by reference.
+pub trait AsRefs<'a> {
+    /// The same anonymous struct, with references to the values of fields,
+    /// e.g. `Struct!{ red: &'a u8 }` for `Struct!{ red: u8 }`.
+    type Refs;
+    fn as_refs(&'a self) -> Self::Refs;
+}
+
+/// An anonymous struct whose fields may be borrowed mutably.
+pub trait AsMuts<'a> {
+    /// The same anonymous struct, with mutable references to the values of fields,
+    /// e.g. `Struct!{ red: &'a mut u8 }` for `Struct!{ red: u8 }`.
+    type Muts;
+    fn as_muts(&'a mut self) -> Self::Muts;
+}
+
+/// An anonymous struct that may be converted into anonymous struct `Target`, with
+/// the same fields in a different order.
+///
+/// This is typically useful to convert anonymous structs built by hand into canonical
+/// order, e.g. `let color: Struct!{ red: u8, blue: u8 } = (Named::<"red", _>(0), (Named::<"blue", _>(2), ())).reorder();`.
+///
+/// `Indices` is the position of each field of `Target` in `Self`. It is inferred by
+/// the compiler.
+pub trait Reorder<Target, Indices> {
+    fn reorder(self) -> Target;
+}
+
+impl IntoAnonymous for () {
+    type Anonymous = Self;
+    fn into_anonymous(self) -> Self {
+        self
+    }
+}
+
+impl<A: OwnedField, R: IntoAnonymous<Anonymous = R>> IntoAnonymous for (A, R) {
+    type Anonymous = Self;
+    fn into_anonymous(self) -> Self {
+        self
+    }
+}
+
+impl<'a> AsRefs<'a> for () {
+    type Refs = ();
+    fn as_refs(&'a self) {}
+}
+
+impl<'a, const N: &'static str, T: 'a, R: AsRefs<'a>> AsRefs<'a> for (Named<N, T>, R) {
+    type Refs = (Named<N, &'a T>, R::Refs);
+    fn as_refs(&'a self) -> Self::Refs {
+        (Named(&self.0.0), self.1.as_refs())
+    }
+}
+
+impl<'a> AsMuts<'a> for () {
+    type Muts = ();
+    fn as_muts(&'a mut self) {}
+}
+
+impl<'a, const N: &'static str, T: 'a, R: AsMuts<'a>> AsMuts<'a> for (Named<N, T>, R) {
+    type Muts = (Named<N, &'a mut T>, R::Muts);
+    fn as_muts(&'a mut self) -> Self::Muts {
+        (Named(&mut self.0.0), self.1.as_muts())
+    }
+}
+
+impl Reorder<(), ()> for () {
+    fn reorder(self) {}
+}
+
+impl<S, const N: &'static str, T, R, I, RI> Reorder<(Named<N, T>, R), (I, RI)> for S
+    where S: Has<N, T, I>,
+          S::Rest: Reorder<R, RI>,
+{
+    fn reorder(self) -> (Named<N, T>, R) {
+        let (value, rest) = self.remove();
+        (Named(value), rest.reorder())
+    }
+}
+
+/// `nested!(A B C)` is `(A, (B, (C, ())))`, the representation of an anonymous struct
+/// with fields `A`, `B` and `C`, in type, pattern or expression position.
+macro_rules! nested {
+    () => { () };
+    ($first:ident $($rest:ident)*) => { ($first, nested!($($rest)*)) };
+}
+
+/// `nested_named!(N0 T0 N1 T1)` is `(Named<N0, T0>, (Named<N1, T1>, ()))`.
+macro_rules! nested_named {
+    () => { () };
+    ($name:ident $value:ident $($rest:ident)*) => { (Named<$name, $value>, nested_named!($($rest)*)) };
+}
+
+/// Implement `IntoTuple` and `FromTuple` for an anonymous struct with a given number of fields.
+///
+/// Unlike other traits, these cannot be implemented recursively, as they produce flat tuples.
+macro_rules! impl_convert {
+    ($($field:ident $name:ident $value:ident)*) => {
+        // Without fields, the tuple is `()`.
+        #[allow(non_snake_case, clippy::unused_unit)]
+        impl<$($field: OwnedField,)*> IntoTuple for nested!($($field)*) {
+            type Tuple = ($($field::Value,)*);
+            fn into_tuple(self) -> Self::Tuple {
+                let nested!($($field)*) = self;
+                ($($field.take(),)*)
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<$(const $name: &'static str, $value,)*> FromTuple for nested_named!($($name $value)*) {
+            fn from_tuple(($($value,)*): Self::Tuple) -> Self {
+                $(
+                    let $value = Named($value);
+                )*
+                nested!($($value)*)
+            }
+        }
+    };
+}
+
+impl_convert!();
+impl_convert!(A0 N0 T0);
+impl_convert!(A0 N0 T0 A1 N1 T1);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4 A5 N5 T5);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4 A5 N5 T5 A6 N6 T6);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4 A5 N5 T5 A6 N6 T6 A7 N7 T7);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4 A5 N5 T5 A6 N6 T6 A7 N7 T7 A8 N8 T8);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4 A5 N5 T5 A6 N6 T6 A7 N7 T7 A8 N8 T8 A9 N9 T9);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4 A5 N5 T5 A6 N6 T6 A7 N7 T7 A8 N8 T8 A9 N9 T9 A10 N10 T10);
+impl_convert!(A0 N0 T0 A1 N1 T1 A2 N2 T2 A3 N3 T3 A4 N4 T4 A5 N5 T5 A6 N6 T6 A7 N7 T7 A8 N8 T8 A9 N9 T9 A10 N10 T10 A11 N11 T11);
@@ -0,0 +1,224 @@
+//! Looking up fields by name in anonymous structs.
+
+use std::marker::PhantomData;
+
+use crate::{FieldList, FieldNames, Named};
+
+/// The position of a field in an anonymous struct: the first field.
+pub struct Here;
+
+/// The position of a field in an anonymous struct: after the first field, at position `I`
+/// among the remaining fields.
+pub struct There<I>(I);
+
+/// An anonymous struct with a field `NAME` of type `T`.
+///
+/// This is what `{ NAME: T, .. }` means: any anonymous struct containing field `NAME`,
+/// regardless of its other fields.
+///
+/// `Index` is the position of the field in the anonymous struct. It is inferred by the
+/// compiler, so it should simply be a generic parameter:
+///
+/// ```ignore
+/// fn area<I, J>(s: impl Has<"width", f64, I> + Has<"height", f64, J>) -> f64 { ... }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "anonymous struct has no field {NAME}",
+    label = "no field {NAME}",
+    note = "the available fields are `{Self}`",
+)]
+pub trait Has<const NAME: &'static str, T, Index> {
+    /// The other fields of this anonymous struct.
+    type Rest;
+
+    /// This anonymous struct, with a value of type `U` for field `NAME`.
+    type Mapped<U>;
+
+    /// Remove field `NAME`, returning its value and the other fields.
+    fn remove(self) -> (T, Self::Rest);
+
+    /// Borrow the value of field `NAME`.
+    fn get(&self) -> &T;
+
+    /// Borrow the value of field `NAME` mutably.
+    fn get_mut(&mut self) -> &mut T;
+
+    /// Replace the value `v` of field `NAME` with `f(v)`, keeping the other fields.
+    fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Self::Mapped<U>;
+
+    /// Take the value of field `NAME`, dropping the other fields.
+    fn take(self) -> T where Self: Sized {
+        self.remove().0
+    }
+}
+
+impl<const N: &'static str, T, I, S> Has<N, T, I> for S
+    where S: HasIn<S, N, T, I>,
+{
+    type Rest = S::Rest;
+    type Mapped<U> = S::Mapped<U>;
+    fn remove(self) -> (T, Self::Rest) {
+        S::remove_in(self)
+    }
+    fn get(&self) -> &T {
+        S::get_in(self)
+    }
+    fn get_mut(&mut self) -> &mut T {
+        S::get_mut_in(self)
+    }
+    fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Self::Mapped<U> {
+        S::map_in(self, f)
+    }
+}
+
+/// An anonymous struct with a field `NAME` of type `T`, and other fields `Rest`.
+///
+/// This is `Has`, with the other fields as a parameter rather than an associated type. This
+/// is what `destruct!`, `pick!`, `omit!` and `rename!` use: if field `NAME` is missing, the
+/// compiler reports it once, rather than once more wherever the other fields are used.
+///
+/// `Whole` is the anonymous struct from which fields are removed, whose fields the error
+/// message lists.
+pub trait RemoveField<const NAME: &'static str, T, Index, Rest, Whole> {
+    /// Remove field `NAME`, returning its value and the other fields.
+    ///
+    /// `whole` comes first, so that the compiler knows `Whole` before it looks up `NAME`.
+    fn remove_field(whole: &FieldsOf<Whole>, fields: Self) -> (T, Rest);
+}
+
+impl<const N: &'static str, T, I, R, S, W, WN> RemoveField<N, T, I, R, W> for S
+    where W: FieldNames<Names = WN>,
+          WN: FieldList,
+          FieldsOf<W>: FieldIn<{ WN::FIELD_LIST }, S, N, T, I, R>,
+{
+    fn remove_field(_: &FieldsOf<W>, fields: S) -> (T, R) {
+        <FieldsOf<W> as FieldIn<{ WN::FIELD_LIST }, S, N, T, I, R>>::remove_field_in(fields)
+    }
+}
+
+/// The type of anonymous struct `S`, from which `RemoveField` removes fields.
+pub struct FieldsOf<S>(PhantomData<S>);
+
+impl<S> FieldsOf<S> {
+    pub fn of(_: &S) -> Self {
+        FieldsOf(PhantomData)
+    }
+}
+
+/// The lookup of field `NAME` of type `T` in `Fields`, the last fields of an anonymous
+/// struct with fields `FIELDS`, leaving other fields `Rest`.
+///
+/// This is how `RemoveField` is implemented, for `FieldsOf<W>`, where `W` is the whole
+/// anonymous struct. Unlike the fields, this is never the type that `RemoveField` is
+/// implemented for, so, if no field is found, the compiler reports the failure with the
+/// error message of this trait, which lists the fields of `W`.
+#[diagnostic::on_unimplemented(
+    message = "anonymous struct has no field {NAME}",
+    label = "no field {NAME}",
+    note = "the available fields are {FIELDS}",
+)]
+pub trait FieldIn<const FIELDS: &'static str, Fields, const NAME: &'static str, T, Index, Rest> {
+    /// Remove field `NAME` from `fields`, returning its value and the other fields.
+    fn remove_field_in(fields: Fields) -> (T, Rest);
+}
+
+impl<W, const FIELDS: &'static str, const N: &'static str, T, R> FieldIn<FIELDS, (Named<N, T>, R), N, T, Here, R> for FieldsOf<W> {
+    fn remove_field_in(fields: (Named<N, T>, R)) -> (T, R) {
+        (fields.0.0, fields.1)
+    }
+}
+
+impl<W, const FIELDS: &'static str, const N: &'static str, T, I, A, R, RR> FieldIn<FIELDS, (A, R), N, T, There<I>, (A, RR)> for FieldsOf<W>
+    where FieldsOf<W>: FieldIn<FIELDS, R, N, T, I, RR>,
+{
+    fn remove_field_in(fields: (A, R)) -> (T, (A, RR)) {
+        let (value, rest) = <FieldsOf<W> as FieldIn<FIELDS, R, N, T, I, RR>>::remove_field_in(fields.1);
+        (value, (fields.0, rest))
+    }
+}
+
+/// The lookup of field `NAME` of type `T` in `Fields`, the last fields of anonymous
+/// struct `Self`.
+///
+/// This is how `Has` is implemented. The lookup recurses on `Fields` rather than on
+/// `Self`, so that, if no field is found, the compiler reports the failure on the whole
+/// anonymous struct, with the error message of `Has`, rather than on `()`.
+#[diagnostic::on_unimplemented(
+    message = "anonymous struct has no field {NAME}",
+    label = "no field {NAME}",
+    note = "the available fields are `{Self}`",
+)]
+pub trait HasIn<Fields, const NAME: &'static str, T, Index> {
+    /// `Fields`, without field `NAME`.
+    type Rest;
+
+    /// `Fields`, with a value of type `U` for field `NAME`.
+    type Mapped<U>;
+
+    /// Remove field `NAME` from `fields`, returning its value and the other fields.
+    fn remove_in(fields: Fields) -> (T, Self::Rest);
+
+    /// Borrow the value of field `NAME` in `fields`.
+    fn get_in(fields: &Fields) -> &T;
+
+    /// Borrow the value of field `NAME` in `fields` mutably.
+    fn get_mut_in(fields: &mut Fields) -> &mut T;
+
+    /// Replace the value `v` of field `NAME` in `fields` with `f(v)`.
+    fn map_in<U, F: FnOnce(T) -> U>(fields: Fields, f: F) -> Self::Mapped<U>;
+}
+
+impl<S: ?Sized, const N: &'static str, T, R> HasIn<(Named<N, T>, R), N, T, Here> for S {
+    type Rest = R;
+    type Mapped<U> = (Named<N, U>, R);
+    fn remove_in(fields: (Named<N, T>, R)) -> (T, R) {
+        (fields.0.0, fields.1)
+    }
+    fn get_in(fields: &(Named<N, T>, R)) -> &T {
+        &fields.0.0
+    }
+    fn get_mut_in(fields: &mut (Named<N, T>, R)) -> &mut T {
+        &mut fields.0.0
+    }
+    fn map_in<U, F: FnOnce(T) -> U>(fields: (Named<N, T>, R), f: F) -> Self::Mapped<U> {
+        (Named(f(fields.0.0)), fields.1)
+    }
+}
+
+impl<S: ?Sized, const N: &'static str, T, I, A, R> HasIn<(A, R), N, T, There<I>> for S
+    where S: HasIn<R, N, T, I>,
+{
+    type Rest = (A, <S as HasIn<R, N, T, I>>::Rest);
+    type Mapped<U> = (A, <S as HasIn<R, N, T, I>>::Mapped<U>);
+    fn remove_in(fields: (A, R)) -> (T, Self::Rest) {
+        let (value, rest) = S::remove_in(fields.1);
+        (value, (fields.0, rest))
+    }
+    fn get_in(fields: &(A, R)) -> &T {
+        S::get_in(&fields.1)
+    }
+    fn get_mut_in(fields: &mut (A, R)) -> &mut T {
+        S::get_mut_in(&mut fields.1)
+    }
+    fn map_in<U, F: FnOnce(T) -> U>(fields: (A, R), f: F) -> Self::Mapped<U> {
+        (fields.0, S::map_in(fields.1, f))
+    }
+}
+
+/// Borrowing a field mutably by name, as `s.field` does for regular structs.
+///
+/// As a method, this goes through references, so that `s.get_field_mut::<"x", _, _>()`
+/// works whether `s` is an anonymous struct or a (mutable) reference to one. This is
+/// what `set!` and `replace!` use.
+pub trait GetFieldMut {
+    /// Borrow the value of field `NAME` mutably.
+    fn get_field_mut<const NAME: &'static str, T, Index>(&mut self) -> &mut T
+        where Self: Has<NAME, T, Index>
+    {
+        Has::get_mut(self)
+    }
+}
+
+// Only for anonymous structs, so that method calls go through references.
+impl GetFieldMut for () {}
+impl<A, R> GetFieldMut for (A, R) {}
@@ -1,6 +1,124 @@
-#![feature(associated_const_equality)]
+#![feature(adt_const_params, unsized_const_params, generic_const_exprs)]
+#![allow(incomplete_features)]
 
-pub use obstruct_macros::{call, destruct, instruct};
+pub use obstruct_macros::Obstruct;
+
+// The macros are wrappers around those of `obstruct_macros` which pass along `$crate`,
+// so that the generated code works even if `obstruct` is renamed or re-exported.
+
+/// Build an anonymous struct, e.g. `instruct!{ x: 7, y: 9 }`.
+///
+/// See the documentation of [`obstruct_macros::instruct`].
+#[macro_export]
+macro_rules! instruct {
+    ($($tokens:tt)*) => { $crate::__private::instruct!{ crate = $crate; $($tokens)* } };
+}
+
+/// The type of anonymous structs, e.g. `Struct!{ x: u32, y: f64 }`.
+///
+/// See the documentation of [`obstruct_macros::Struct`].
+#[macro_export]
+macro_rules! Struct {
+    ($($tokens:tt)*) => { $crate::__private::Struct!{ crate = $crate; $($tokens)* } };
+}
+
+/// Call a function with named arguments, e.g. `call!{ foo {x: 7, y: 9} }`.
+///
+/// See the documentation of [`obstruct_macros::call`].
+#[macro_export]
+macro_rules! call {
+    ($($tokens:tt)*) => { $crate::__private::call!{ crate = $crate; $($tokens)* } };
+}
+
+/// Destructure an anonymous struct, e.g. `destruct!{let {x, y} = foo}`, or declare a function with named arguments.
+///
+/// See the documentation of [`obstruct_macros::destruct`].
+#[macro_export]
+macro_rules! destruct {
+    ($($tokens:tt)*) => { $crate::__private::destruct!{ crate = $crate; $($tokens)* } };
+}
+
+/// Keep only some fields of an anonymous struct, e.g. `pick!(foo, {x, y})`.
+///
+/// See the documentation of [`obstruct_macros::pick`].
+#[macro_export]
+macro_rules! pick {
+    ($($tokens:tt)*) => { $crate::__private::pick!{ crate = $crate; $($tokens)* } };
+}
+
+/// Remove some fields of an anonymous struct, e.g. `omit!(foo, {x, y})`.
+///
+/// See the documentation of [`obstruct_macros::omit`].
+#[macro_export]
+macro_rules! omit {
+    ($($tokens:tt)*) => { $crate::__private::omit!{ crate = $crate; $($tokens)* } };
+}
+
+/// Rename fields of an anonymous struct, e.g. `rename!(foo, {x => a, y => b})`.
+///
+/// See the documentation of [`obstruct_macros::rename`].
+#[macro_export]
+macro_rules! rename {
+    ($($tokens:tt)*) => { $crate::__private::rename!{ crate = $crate; $($tokens)* } };
+}
+
+/// Transform one field of an anonymous struct, e.g. `map_field!(foo, x => f)`.
+///
+/// See the documentation of [`obstruct_macros::map_field`].
+#[macro_export]
+macro_rules! map_field {
+    ($($tokens:tt)*) => { $crate::__private::map_field!{ crate = $crate; $($tokens)* } };
+}
+
+/// Set one field of an anonymous struct, e.g. `set!(foo.x = value)`.
+///
+/// See the documentation of [`obstruct_macros::set`].
+#[macro_export]
+macro_rules! set {
+    ($($tokens:tt)*) => { $crate::__private::set!{ crate = $crate; $($tokens)* } };
+}
+
+/// Set one field of an anonymous struct, returning the previous value, e.g. `replace!(foo.x = value)`.
+///
+/// See the documentation of [`obstruct_macros::replace`].
+#[macro_export]
+macro_rules! replace {
+    ($($tokens:tt)*) => { $crate::__private::replace!{ crate = $crate; $($tokens)* } };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use obstruct_macros::*;
+    pub use crate::cfg::{Cfg, Resolve};
+}
+
+mod cfg;
+
+mod has;
+pub use has::{FieldIn, FieldsOf, GetFieldMut, Has, HasIn, Here, RemoveField, There};
+
+mod reflect;
+pub use reflect::{AnonymousStruct, FieldList, FieldNames, Visit, Visitor};
+
+mod named;
+pub use named::Named;
+
+mod convert;
+pub use convert::{AsMuts, AsRefs, FromTuple, IntoAnonymous, IntoTuple, Reorder};
+
+mod mapping;
+pub use mapping::MapAll;
+
+mod check;
+pub use check::{ArgIn, HasArg, NoOtherArg, NoOtherField, OtherArgsIn, OtherFieldsIn, PlainArgIn, PlainOtherArgsIn};
+
+mod merge;
+pub use merge::{Insert, InsertOverride, Merge, MergeInto, MergeOverride};
+
+#[cfg(feature = "serde")]
+mod map;
+#[cfg(feature = "serde")]
+pub use map::{AsMap, DeserializeFields, SerializeFields};
 
 /// A field in an anonymous struct.
 pub trait Field<T> {
@@ -8,6 +126,17 @@ pub trait Field<T> {
     fn take(self) -> T;
 }
 
+/// A field owned by an anonymous struct.
+///
+/// Unlike `Field`, which is also implemented by references to fields, this
+/// determines the type of the value.
+pub trait OwnedField: Field<Self::Value> {
+    const NAME: &'static str;
+    type Value;
+    fn get(&self) -> &Self::Value;
+    fn get_mut(&mut self) -> &mut Self::Value;
+}
+
 #[doc = include_str!("../../../README.md")]
 #[cfg(doctest)]
 pub struct ReadmeDoctests;
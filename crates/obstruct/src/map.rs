@@ -0,0 +1,154 @@
+//! Serializing anonymous structs as maps, with feature `serde`.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{AnonymousStruct, Named, OwnedField};
+
+/// An anonymous struct, serialized as a map from field names to values.
+///
+/// Anonymous structs are represented as nested tuples, which serde serializes as sequences,
+/// so they need to be wrapped in `AsMap` to be serialized as maps.
+///
+/// Any anonymous struct whose values implement `Serialize` may be serialized.
+/// Only anonymous struct types that may be named, i.e. `Struct!{ ... }`, may be
+/// deserialized.
+///
+/// Values are serialized with their own `Serialize` implementation, so nested anonymous
+/// structs need to be wrapped in `AsMap` as well, e.g. `Struct!{ pos: AsMap<Struct!{ x: u8 }> }`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AsMap<S>(pub S);
+
+/// An anonymous struct whose fields may be serialized.
+pub trait SerializeFields: AnonymousStruct {
+    /// Serialize each field as an entry of `map`.
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error>;
+}
+
+/// An anonymous struct whose fields may be deserialized.
+pub trait DeserializeFields<'de>: AnonymousStruct + Sized {
+    /// Deserialize each field from an entry of `map`, in any order.
+    ///
+    /// Unknown fields are ignored.
+    fn deserialize_fields<A: MapAccess<'de>>(map: A) -> Result<Self, A::Error>;
+}
+
+impl<S: SerializeFields> Serialize for AsMap<S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut map = serializer.serialize_map(Some(S::LEN))?;
+        self.0.serialize_fields(&mut map)?;
+        map.end()
+    }
+}
+
+impl<'de, S: DeserializeFields<'de>> Deserialize<'de> for AsMap<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor<S>(PhantomData<S>);
+        impl<'de, S: DeserializeFields<'de>> de::Visitor<'de> for MapVisitor<S> {
+            type Value = S;
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "an anonymous struct with fields {}", S::FIELD_NAMES.join(", "))
+            }
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<S, A::Error> {
+                S::deserialize_fields(map)
+            }
+        }
+        deserializer.deserialize_map(MapVisitor(PhantomData)).map(AsMap)
+    }
+}
+
+/// Deserialize a field name into its index in `FIELD_NAMES`, or `None` for an unknown field.
+struct FieldIndex<'a>(&'a [&'static str]);
+impl<'de> DeserializeSeed<'de> for FieldIndex<'_> {
+    type Value = Option<usize>;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+impl<'de> de::Visitor<'de> for FieldIndex<'_> {
+    type Value = Option<usize>;
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a field name")
+    }
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|name| *name == value))
+    }
+}
+
+impl SerializeFields for () {
+    fn serialize_fields<M: SerializeMap>(&self, _: &mut M) -> Result<(), M::Error> {
+        Ok(())
+    }
+}
+
+impl<A: OwnedField, R: SerializeFields> SerializeFields for (A, R)
+    where A::Value: Serialize,
+          [&'static str; R::LEN + 1]: Sized,
+{
+    fn serialize_fields<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry(<A as OwnedField>::NAME, self.0.get())?;
+        self.1.serialize_fields(map)
+    }
+}
+
+/// Deserialize the fields of an anonymous struct one at a time, in any order.
+///
+/// This is not exported: it only serves to implement `DeserializeFields`.
+pub trait FieldSlots<'de>: Sized {
+    /// The values deserialized so far, e.g. `(Option<T0>, (Option<T1>, ()))`.
+    type Slots: Default;
+
+    /// Deserialize the next value of `map` as the field at position `index`.
+    fn deserialize_slot<A: MapAccess<'de>>(slots: &mut Self::Slots, index: usize, map: &mut A) -> Result<(), A::Error>;
+
+    /// Build the anonymous struct, once all entries of the map have been deserialized.
+    fn from_slots<E: de::Error>(slots: Self::Slots) -> Result<Self, E>;
+}
+
+impl<'de> FieldSlots<'de> for () {
+    type Slots = ();
+    fn deserialize_slot<A: MapAccess<'de>>(_: &mut (), _: usize, map: &mut A) -> Result<(), A::Error> {
+        map.next_value::<IgnoredAny>()?;
+        Ok(())
+    }
+    fn from_slots<E: de::Error>(_: ()) -> Result<Self, E> {
+        Ok(())
+    }
+}
+
+impl<'de, const N: &'static str, T: Deserialize<'de>, R: FieldSlots<'de>> FieldSlots<'de> for (Named<N, T>, R) {
+    type Slots = (Option<T>, R::Slots);
+    fn deserialize_slot<A: MapAccess<'de>>(slots: &mut Self::Slots, index: usize, map: &mut A) -> Result<(), A::Error> {
+        if index > 0 {
+            return R::deserialize_slot(&mut slots.1, index - 1, map);
+        }
+        if slots.0.is_some() {
+            return Err(de::Error::duplicate_field(N));
+        }
+        slots.0 = Some(map.next_value()?);
+        Ok(())
+    }
+    fn from_slots<E: de::Error>(slots: Self::Slots) -> Result<Self, E> {
+        let value = slots.0.ok_or_else(|| E::missing_field(N))?;
+        Ok((Named(value), R::from_slots(slots.1)?))
+    }
+}
+
+impl<'de, S: AnonymousStruct + FieldSlots<'de>> DeserializeFields<'de> for S {
+    fn deserialize_fields<A: MapAccess<'de>>(mut map: A) -> Result<Self, A::Error> {
+        let mut slots = S::Slots::default();
+        while let Some(index) = map.next_key_seed(FieldIndex(Self::FIELD_NAMES))? {
+            match index {
+                Some(index) => S::deserialize_slot(&mut slots, index, &mut map)?,
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        S::from_slots(slots)
+    }
+}
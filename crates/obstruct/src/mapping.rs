@@ -0,0 +1,27 @@
+//! Transforming the values of all fields of anonymous structs.
+
+use crate::Named;
+
+/// An anonymous struct whose fields all have values of type `T`.
+pub trait MapAll<T> {
+    /// This anonymous struct, with values of type `U`.
+    type Mapped<U>;
+
+    /// Replace the value `v` of each field with `f(v)`, in canonical order.
+    ///
+    /// For instance, `instruct!{ red: 0, green: 1 }.map_all(|v| v * 2)` has `red: 0` and `green: 2`.
+    fn map_all<U, F: FnMut(T) -> U>(self, f: F) -> Self::Mapped<U>;
+}
+
+impl<T> MapAll<T> for () {
+    type Mapped<U> = ();
+    fn map_all<U, F: FnMut(T) -> U>(self, _: F) {}
+}
+
+impl<const N: &'static str, T, R: MapAll<T>> MapAll<T> for (Named<N, T>, R) {
+    type Mapped<U> = (Named<N, U>, R::Mapped<U>);
+    fn map_all<U, F: FnMut(T) -> U>(self, mut f: F) -> Self::Mapped<U> {
+        let head = Named(f(self.0.0));
+        (head, self.1.map_all(f))
+    }
+}
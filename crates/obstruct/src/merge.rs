@@ -0,0 +1,284 @@
+//! Combining anonymous structs.
+//!
+//! Merging inserts each field of the right-hand side into the left-hand side, at its
+//! position in canonical order, so that merging anonymous structs in canonical order, such
+//! as those produced by `instruct!`, produces an anonymous struct in canonical order.
+//!
+//! Field names are compared at compile-time by a `const fn`. As associated const equality
+//! doesn't select impls on the pinned nightly, the result of the comparison is turned into a
+//! type, `Bool<true>` or `Bool<false>`, which requires `generic_const_exprs`, also in the
+//! crates that merge anonymous structs. Without it, `Insert` silently fails to apply, so
+//! `merge!`, `merge_override!` and `rename!` check that the feature is enabled first.
+
+use std::cmp::Ordering;
+
+use crate::Named;
+
+/// Compare field names `a` and `b` in canonical order, i.e. byte by byte, as `str` does.
+pub const fn compare_names(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        if a[i] != b[i] {
+            return if a[i] < b[i] { Ordering::Less } else { Ordering::Greater };
+        }
+        i += 1;
+    }
+    if a.len() < b.len() {
+        Ordering::Less
+    } else if a.len() > b.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Whether field name `a` comes before field name `b` in canonical order.
+pub const fn is_less(a: &str, b: &str) -> bool {
+    matches!(compare_names(a, b), Ordering::Less)
+}
+
+/// Whether field names `a` and `b` are the same.
+pub const fn is_equal(a: &str, b: &str) -> bool {
+    matches!(compare_names(a, b), Ordering::Equal)
+}
+
+/// A boolean, as a type.
+pub struct Bool<const B: bool>;
+
+/// Field names `A` and `B`, to compare them at compile-time with `CompareNames`.
+pub struct Names<const A: &'static str, const B: &'static str>;
+
+/// The comparison of two field names in canonical order.
+pub trait CompareNames {
+    /// Whether the first name comes before the second one, as `Bool<true>` or `Bool<false>`.
+    type Less;
+
+    /// Whether both names are the same, as `Bool<true>` or `Bool<false>`.
+    type Equal;
+}
+
+impl<const A: &'static str, const B: &'static str> CompareNames for Names<A, B>
+    where Bool<{ is_less(A, B) }>: Sized,
+          Bool<{ is_equal(A, B) }>: Sized,
+{
+    type Less = Bool<{ is_less(A, B) }>;
+    type Equal = Bool<{ is_equal(A, B) }>;
+}
+
+/// The position at which a field is inserted into an anonymous struct: before the first
+/// field, if any.
+pub struct Before;
+
+/// The position at which a field is inserted into an anonymous struct: instead of the
+/// first field, which has the same name.
+pub struct Instead;
+
+/// The position at which a field is inserted into an anonymous struct: after the first
+/// field, at position `I` among the remaining fields.
+pub struct After<I>(I);
+
+/// An anonymous struct, without field `NAME`, into which field `NAME` may be inserted.
+///
+/// `Position` is where the field is inserted. It is inferred by the compiler.
+#[diagnostic::on_unimplemented(
+    message = "anonymous struct already has a field {NAME}",
+    label = "field {NAME} is already in `{Self}`",
+)]
+pub trait Insert<const NAME: &'static str, T, Position> {
+    /// This anonymous struct, with field `NAME`.
+    type Output;
+
+    /// Insert field `NAME` with value `value`.
+    fn insert(self, value: T) -> Self::Output;
+}
+
+impl<const M: &'static str, U> Insert<M, U, Before> for () {
+    type Output = (Named<M, U>, ());
+    fn insert(self, value: U) -> Self::Output {
+        (Named(value), ())
+    }
+}
+
+impl<const M: &'static str, U, const N: &'static str, T, R> Insert<M, U, Before> for (Named<N, T>, R)
+    where Names<M, N>: CompareNames<Less = Bool<true>>,
+{
+    type Output = (Named<M, U>, Self);
+    fn insert(self, value: U) -> Self::Output {
+        (Named(value), self)
+    }
+}
+
+impl<const M: &'static str, U, const N: &'static str, T, R, I> Insert<M, U, After<I>> for (Named<N, T>, R)
+    where Names<M, N>: CompareNames<Less = Bool<false>, Equal = Bool<false>>,
+          R: Insert<M, U, I>,
+{
+    type Output = (Named<N, T>, R::Output);
+    fn insert(self, value: U) -> Self::Output {
+        (self.0, self.1.insert(value))
+    }
+}
+
+/// An anonymous struct into which field `NAME` may be inserted, replacing the field with
+/// the same name, if any.
+///
+/// `Position` is where the field is inserted. It is inferred by the compiler.
+pub trait InsertOverride<const NAME: &'static str, T, Position> {
+    /// This anonymous struct, with field `NAME` of type `T`.
+    type Output;
+
+    /// Insert field `NAME` with value `value`, dropping the previous value, if any.
+    fn insert_override(self, value: T) -> Self::Output;
+}
+
+impl<const M: &'static str, U> InsertOverride<M, U, Before> for () {
+    type Output = (Named<M, U>, ());
+    fn insert_override(self, value: U) -> Self::Output {
+        (Named(value), ())
+    }
+}
+
+impl<const M: &'static str, U, const N: &'static str, T, R> InsertOverride<M, U, Before> for (Named<N, T>, R)
+    where Names<M, N>: CompareNames<Less = Bool<true>>,
+{
+    type Output = (Named<M, U>, Self);
+    fn insert_override(self, value: U) -> Self::Output {
+        (Named(value), self)
+    }
+}
+
+impl<const M: &'static str, U, const N: &'static str, T, R> InsertOverride<M, U, Instead> for (Named<N, T>, R)
+    where Names<M, N>: CompareNames<Equal = Bool<true>>,
+{
+    type Output = (Named<M, U>, R);
+    fn insert_override(self, value: U) -> Self::Output {
+        (Named(value), self.1)
+    }
+}
+
+impl<const M: &'static str, U, const N: &'static str, T, R, I> InsertOverride<M, U, After<I>> for (Named<N, T>, R)
+    where Names<M, N>: CompareNames<Less = Bool<false>, Equal = Bool<false>>,
+          R: InsertOverride<M, U, I>,
+{
+    type Output = (Named<N, T>, R::Output);
+    fn insert_override(self, value: U) -> Self::Output {
+        (self.0, self.1.insert_override(value))
+    }
+}
+
+/// An anonymous struct that may be merged with anonymous struct `B`, into `Output`.
+///
+/// Both anonymous structs must have distinct field names. This is checked at
+/// compile-time.
+///
+/// `Indices` is the position at which each field of `B` is inserted and `Output` is the
+/// anonymous struct with the fields of `Self` and `B`. Both are inferred by the compiler.
+pub trait Merge<B, Indices, Output> {
+    /// Merge `self` with `other`, producing an anonymous struct with the fields of both.
+    ///
+    /// If both anonymous structs are in canonical order, e.g. if they were produced by
+    /// `instruct!`, so is the result.
+    fn merge(self, other: B) -> Output;
+}
+
+impl<S, B, I, O> Merge<B, I, O> for S where B: MergeInto<S, I, O> {
+    fn merge(self, other: B) -> O {
+        other.merge_into(self)
+    }
+}
+
+/// An anonymous struct whose fields may be inserted into anonymous struct `S`, producing
+/// `Output`.
+///
+/// This is `Merge` the other way around. As the compiler reports a missing `Insert` with
+/// its own message, which names the field, when the anonymous struct that lacks it is not
+/// the one that is merged into, `merge!` uses this trait.
+pub trait MergeInto<S, Indices, Output> {
+    /// Insert the fields of `self` into `into`.
+    fn merge_into(self, into: S) -> Output;
+}
+
+impl<S> MergeInto<S, (), S> for () {
+    fn merge_into(self, into: S) -> S {
+        into
+    }
+}
+
+impl<S, const N: &'static str, T, R, I, RI, O> MergeInto<S, (I, RI), O> for (Named<N, T>, R)
+    where S: Insert<N, T, I>,
+          R: MergeInto<S::Output, RI, O>,
+{
+    fn merge_into(self, into: S) -> O {
+        self.1.merge_into(into.insert(self.0.0))
+    }
+}
+
+/// An anonymous struct whose fields may be overridden by those of anonymous struct `B`,
+/// into `Output`.
+///
+/// `Indices` is the position at which each field of `B` is inserted and `Output` is the
+/// anonymous struct with the fields of `Self` and `B`, with the types of `B` for the fields
+/// of both. Both are inferred by the compiler.
+pub trait MergeOverride<B, Indices, Output> {
+    /// Merge `self` with `other`, producing an anonymous struct with the fields of both.
+    /// For fields of both, the values of `other` replace those of `self`, possibly with a
+    /// different type.
+    ///
+    /// As with `merge`, if both anonymous structs are in canonical order, so is the result.
+    fn merge_override(self, other: B) -> Output;
+}
+
+impl<S> MergeOverride<(), (), S> for S {
+    fn merge_override(self, _: ()) -> S {
+        self
+    }
+}
+
+impl<S, const N: &'static str, T, R, I, RI, O> MergeOverride<(Named<N, T>, R), (I, RI), O> for S
+    where S: InsertOverride<N, T, I>,
+          S::Output: MergeOverride<R, RI, O>,
+{
+    fn merge_override(self, other: (Named<N, T>, R)) -> O {
+        self.insert_override(other.0.0).merge_override(other.1)
+    }
+}
+
+/// Fail with an error that names `generic_const_exprs` unless the calling crate enables it.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __require_generic_const_exprs {
+    () => {
+        // Comparing field names requires `#![feature(generic_const_exprs)]` in the calling crate.
+        #[allow(dead_code)]
+        struct RequireGenericConstExprs<const N: usize> where [(); N + 1]:;
+    };
+}
+
+/// Merge two anonymous structs with distinct field names.
+///
+/// ```ignore
+/// let color = merge!(instruct!{ red: 0 }, instruct!{ green: 1, blue: 2 });
+/// assert_eq!(color, instruct!{ red: 0, green: 1, blue: 2 });
+/// ```
+#[macro_export]
+macro_rules! merge {
+    ($left:expr, $right:expr $(,)?) => {{
+        $crate::__require_generic_const_exprs!{}
+        let left = $left;
+        $crate::MergeInto::merge_into($right, left)
+    }};
+}
+
+/// Merge two anonymous structs, the right-hand side winning for fields of both.
+///
+/// ```ignore
+/// let defaults = instruct!{ verbose: false, retries: 3 };
+/// let options = merge_override!(defaults, instruct!{ retries: 5, color: true });
+/// ```
+#[macro_export]
+macro_rules! merge_override {
+    ($left:expr, $right:expr $(,)?) => {{
+        $crate::__require_generic_const_exprs!{}
+        $crate::MergeOverride::merge_override($left, $right)
+    }};
+}
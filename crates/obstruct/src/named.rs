@@ -0,0 +1,42 @@
+//! A field wrapper that may be named in types.
+
+use crate::{Field, OwnedField};
+
+/// A field `NAME` with a value of type `T`.
+///
+/// This is the type of all fields created by `instruct!` and `call!`. As it may be named,
+/// anonymous struct types may be named, typically with `Struct!`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Named<const NAME: &'static str, T>(pub T);
+
+impl<const N: &'static str, T> Field<T> for Named<N, T> {
+    const NAME: &'static str = N;
+    fn take(self) -> T {
+        self.0
+    }
+}
+
+impl<'a, const N: &'static str, T> Field<&'a T> for &'a Named<N, T> {
+    const NAME: &'static str = N;
+    fn take(self) -> &'a T {
+        &self.0
+    }
+}
+
+impl<'a, const N: &'static str, T> Field<&'a mut T> for &'a mut Named<N, T> {
+    const NAME: &'static str = N;
+    fn take(self) -> &'a mut T {
+        &mut self.0
+    }
+}
+
+impl<const N: &'static str, T> OwnedField for Named<N, T> {
+    const NAME: &'static str = N;
+    type Value = T;
+    fn get(&self) -> &T {
+        &self.0
+    }
+    fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
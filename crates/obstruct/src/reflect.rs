@@ -0,0 +1,162 @@
+//! Inspecting the fields of anonymous structs.
+//!
+//! The names of the fields are concatenated at compile-time, into an array whose length
+//! depends on the number of fields, which requires `generic_const_exprs`. Crates that use
+//! `FIELD_NAMES` don't need it, but generic impls that need `(A, R): AnonymousStruct` for
+//! some tail `R` must repeat the bound `[&'static str; R::LEN + 1]: Sized`, as `Visit` does.
+//! The same goes for `FIELD_LIST`, which error messages use to list the fields.
+
+use std::any::Any;
+
+use crate::{Named, OwnedField};
+
+/// An anonymous struct.
+pub trait AnonymousStruct {
+    /// The number of fields.
+    const LEN: usize;
+
+    /// The names of the fields, in canonical (alphabetical) order.
+    const FIELD_NAMES: &'static [&'static str];
+
+    /// Call `f` on the name and value of each field, in canonical order.
+    ///
+    /// This requires all fields to be `'static`. For non-`'static` fields or for
+    /// statically typed visitors, see `Visit`.
+    fn for_each_field<F>(&self, mut f: F) where F: FnMut(&'static str, &dyn Any), Self: Visit<F> {
+        self.visit(&mut f)
+    }
+}
+
+/// A visitor for fields with values of type `T`.
+///
+/// Implement this for all the value types you wish to visit, e.g.
+/// `impl<T: Debug> Visitor<T> for MyLogger`.
+pub trait Visitor<T> {
+    fn visit(&mut self, name: &'static str, value: &T);
+}
+
+impl<T: Any, F> Visitor<T> for F where F: FnMut(&'static str, &dyn Any) {
+    fn visit(&mut self, name: &'static str, value: &T) {
+        self(name, value)
+    }
+}
+
+/// An anonymous struct whose fields may be visited by `V`.
+pub trait Visit<V>: AnonymousStruct {
+    /// Call `visitor` on the name and value of each field, in canonical order.
+    fn visit(&self, visitor: &mut V);
+}
+
+/// Field name `head`, followed by field names `tail`, as an array of length `LEN`, which
+/// must be `tail.len() + 1`.
+const fn concat_names<const LEN: usize>(head: &'static str, tail: &'static [&'static str]) -> [&'static str; LEN] {
+    let mut names = [""; LEN];
+    names[0] = head;
+    let mut i = 0;
+    while i < tail.len() {
+        names[i + 1] = tail[i];
+        i += 1;
+    }
+    names
+}
+
+/// The field names of an anonymous struct, as an anonymous struct with fields of type `()`,
+/// e.g. `Struct!{ red: (), x: () }` for `Struct!{ red: u8, x: T }`.
+///
+/// Unlike the anonymous struct, this doesn't depend on the types of fields, so its
+/// `FIELD_LIST` is known even where these types are generic.
+pub trait FieldNames {
+    type Names;
+}
+
+/// The names of the fields of an anonymous struct, as a list for error messages, e.g.
+/// `"blue, green, red"`.
+pub trait FieldList {
+    /// The length of `FIELD_LIST`, in bytes.
+    const FIELD_LIST_LEN: usize;
+
+    /// The names of the fields, in canonical order, separated by `", "`.
+    const FIELD_LIST: &'static str;
+}
+
+/// The length of the list of field names `head`, followed by a list of length `tail_len`.
+const fn field_list_len(head: &'static str, tail_len: usize) -> usize {
+    if tail_len == 0 {
+        head.len()
+    } else {
+        head.len() + ", ".len() + tail_len
+    }
+}
+
+/// The list of field names `head`, followed by list `tail`, as bytes. `LEN` must be
+/// `field_list_len(head, tail.len())`.
+const fn join_names<const LEN: usize>(head: &'static str, tail: &'static str) -> [u8; LEN] {
+    let mut list = [0; LEN];
+    let (head, tail) = (head.as_bytes(), tail.as_bytes());
+    let mut i = 0;
+    while i < head.len() {
+        list[i] = head[i];
+        i += 1;
+    }
+    if !tail.is_empty() {
+        list[i] = b',';
+        list[i + 1] = b' ';
+        i += 2;
+    }
+    let mut j = 0;
+    while j < tail.len() {
+        list[i + j] = tail[j];
+        j += 1;
+    }
+    list
+}
+
+impl FieldNames for () {
+    type Names = ();
+}
+
+impl<const N: &'static str, T, R: FieldNames> FieldNames for (Named<N, T>, R) {
+    type Names = (Named<N, ()>, R::Names);
+}
+
+impl FieldList for () {
+    const FIELD_LIST_LEN: usize = 0;
+    const FIELD_LIST: &'static str = "";
+}
+
+impl<const N: &'static str, T, R: FieldList> FieldList for (Named<N, T>, R)
+    where [u8; field_list_len(N, R::FIELD_LIST_LEN)]: Sized,
+{
+    const FIELD_LIST_LEN: usize = field_list_len(N, R::FIELD_LIST_LEN);
+    const FIELD_LIST: &'static str = match std::str::from_utf8(&join_names::<{ field_list_len(N, R::FIELD_LIST_LEN) }>(N, R::FIELD_LIST)) {
+        Ok(list) => list,
+        Err(_) => unreachable!(),
+    };
+}
+
+impl AnonymousStruct for () {
+    const LEN: usize = 0;
+    const FIELD_NAMES: &'static [&'static str] = &[];
+}
+
+impl<A: OwnedField, R: AnonymousStruct> AnonymousStruct for (A, R)
+    where [&'static str; R::LEN + 1]: Sized,
+{
+    const LEN: usize = R::LEN + 1;
+    const FIELD_NAMES: &'static [&'static str] = &concat_names::<{ R::LEN + 1 }>(<A as OwnedField>::NAME, R::FIELD_NAMES);
+}
+
+impl<V> Visit<V> for () {
+    fn visit(&self, _: &mut V) {}
+}
+
+impl<V, A: OwnedField, R> Visit<V> for (A, R)
+    where V: Visitor<A::Value>,
+          R: Visit<V>,
+          [&'static str; R::LEN + 1]: Sized,
+{
+    fn visit(&self, visitor: &mut V) {
+        Visitor::<A::Value>::visit(visitor, <A as OwnedField>::NAME, self.0.get());
+        self.1.visit(visitor);
+    }
+}
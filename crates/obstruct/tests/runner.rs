@@ -1,4 +1,5 @@
-#![feature(associated_const_equality)]
+#![feature(adt_const_params, unsized_const_params, generic_const_exprs)]
+#![allow(incomplete_features)]
 
 mod should_pass;
 
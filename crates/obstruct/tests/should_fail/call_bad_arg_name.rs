@@ -1,4 +1,3 @@
-#![feature(associated_const_equality)]
 fn struct_bad_field_names() {
     // Check that the code won't build if the field names are incorrect
 
@@ -0,0 +1,13 @@
+#![deny(deprecated)]
+
+fn test_deprecated_arg() {
+    use obstruct_macros::{destruct, call};
+
+    destruct!{#[params] fn resize({width: u32, #[deprecated(note = "use `width`")] size: u32 = 0}) -> u32 { width + size }};
+
+    let _ = call! { #[params] resize, { width: 1 }};
+    let _ = call! { #[params] resize, { size: 1, width: 1 }}; // Deprecated argument.
+}
+
+
+fn main() {}
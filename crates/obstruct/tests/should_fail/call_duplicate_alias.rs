@@ -0,0 +1,11 @@
+
+fn test_duplicate_alias() {
+    use obstruct_macros::{destruct, call};
+
+    destruct!{#[params] fn paint({#[alias(colour)] color: &'static str}) -> usize { color.len() }};
+
+    let _ = call! { #[params] paint, { color: "red", colour: "blue" }}; // Same argument, twice.
+}
+
+
+fn main() {}
@@ -1,4 +1,3 @@
-#![feature(associated_const_equality)]
 
 fn test_missing_args() {
     use obstruct_macros::{destruct, call};
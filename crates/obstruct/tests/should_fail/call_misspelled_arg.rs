@@ -0,0 +1,11 @@
+
+fn test_misspelled_arg() {
+    use obstruct_macros::{destruct, call};
+
+    destruct!{#[params] fn paint({color: &'static str, width: u32}) -> usize { color.len() + width as usize }};
+
+    let _ = call! { #[params] paint, { colr: "red", width: 1 }}; // Misspelled argument.
+}
+
+
+fn main() {}
@@ -0,0 +1,10 @@
+fn test_plain_misspelled_arg() {
+    use obstruct_macros::{destruct, call};
+
+    destruct!{fn paint({color: &'static str, width: u32}) -> usize { color.len() + width as usize }};
+
+    let _ = call! { paint, { colr: "red", width: 1 }}; // Misspelled argument, without `#[params]`.
+}
+
+
+fn main() {}
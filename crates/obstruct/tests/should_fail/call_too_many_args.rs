@@ -1,4 +1,3 @@
-#![feature(associated_const_equality)]
 
 fn test_too_many_args() {
     use obstruct_macros::{destruct, call};
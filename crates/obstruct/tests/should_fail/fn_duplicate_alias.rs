@@ -0,0 +1,9 @@
+
+fn test_duplicate_alias() {
+    use obstruct_macros::destruct;
+
+    destruct!{fn paint({#[alias(colour)] color: &'static str, colour: &'static str}) -> usize { color.len() + colour.len() }};
+}
+
+
+fn main() {}
@@ -0,0 +1,12 @@
+
+fn test_fn_missing_arg() {
+    use obstruct_macros::{destruct, instruct};
+
+    destruct!{#[params] fn test_foo({a: i32, b: f64 = 1.0}) -> f64 { a as f64 + b }};
+
+    // Without `call!`, default values are not filled in.
+    let _ = test_foo(instruct! { a: 1 });
+}
+
+
+fn main() {}
@@ -0,0 +1,7 @@
+
+use obstruct_macros::destruct;
+
+destruct!{fn paint({#[alias(colour)] color: &'static str, #[deprecated] size: u32, width: u32 = 1}) -> u32 { width }}
+
+
+fn main() {}
@@ -0,0 +1,9 @@
+
+struct Shape;
+
+impl Shape {
+    obstruct_macros::destruct!{#[params] fn scale(self, {factor: u32 = 1}) -> u32 { factor }}
+}
+
+
+fn main() {}
@@ -0,0 +1,8 @@
+
+use obstruct_macros::destruct;
+
+// Each combination of `#[cfg]`s requires a version of the function.
+destruct!{fn open({#[cfg(unix)] a: u8, #[cfg(windows)] b: u8, #[cfg(test)] c: u8, #[cfg(debug_assertions)] d: u8, #[cfg(miri)] e: u8}) {}}
+
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![feature(generic_const_exprs)]
+#![allow(incomplete_features)]
+
+fn test_merge_shared_field() {
+    use obstruct::merge;
+    use obstruct_macros::instruct;
+
+    let _ = merge!(instruct! { red: 0, green: 1 }, instruct! { green: 2 }); // `green` is on both sides.
+}
+
+
+fn main() {
+    test_merge_shared_field();
+}
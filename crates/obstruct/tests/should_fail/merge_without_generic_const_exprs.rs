@@ -0,0 +1,11 @@
+fn test_merge_without_generic_const_exprs() {
+    use obstruct::{instruct, merge};
+
+    // `merge!` needs `#![feature(generic_const_exprs)]` to compare field names.
+    let _ = merge!(instruct! { red: 0 }, instruct! { green: 1 });
+}
+
+
+fn main() {
+    test_merge_without_generic_const_exprs();
+}
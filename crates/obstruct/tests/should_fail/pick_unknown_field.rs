@@ -0,0 +1,12 @@
+
+fn test_pick_unknown_field() {
+    use obstruct::{instruct, pick};
+
+    let color = instruct! { red: 0, green: 1.0, blue: "blue" };
+    let _ = pick!(color, {red, oops}); // No field `oops`.
+}
+
+
+fn main() {
+    test_pick_unknown_field();
+}
@@ -0,0 +1,14 @@
+#![feature(generic_const_exprs)]
+#![allow(incomplete_features)]
+
+fn test_rename_collision() {
+    use obstruct::{instruct, rename};
+
+    let shape = instruct! { colour: "red", color: "blue" };
+    let _ = rename!(shape, {colour => color}); // There is already a field `color`.
+}
+
+
+fn main() {
+    test_rename_collision();
+}
@@ -0,0 +1,11 @@
+fn test_rename_without_generic_const_exprs() {
+    use obstruct::{instruct, rename};
+
+    // `rename!` needs `#![feature(generic_const_exprs)]` to compare field names.
+    let _ = rename!(instruct! { colour: "red" }, {colour => color});
+}
+
+
+fn main() {
+    test_rename_without_generic_const_exprs();
+}
@@ -1,4 +1,3 @@
-#![feature(associated_const_equality)]
 
 fn test_instruct_destruct_bad_field_names() {
     use obstruct_macros::{destruct, call};
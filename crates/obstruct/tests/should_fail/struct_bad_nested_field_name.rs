@@ -0,0 +1,11 @@
+fn struct_bad_nested_field_name() {
+    // Check that the code won't build if the field names of a nested struct are incorrect
+
+    use obstruct_macros::{instruct, destruct};
+
+    let shape = instruct! { pos: { x: 1, y: 2 }, color: "red" };
+
+    destruct! { let {pos: {x, oops}, color} = shape }; // Look, we've used oops!
+    // This fails with an error message mentioning oops.
+}
+fn main() {}
@@ -0,0 +1,17 @@
+
+fn test_struct_duplicate_cfg_fields() {
+    use obstruct_macros::{call, destruct, instruct, Struct};
+
+    let color = instruct! { #[cfg(unix)] red: 0, red: 1 }; // `red` is defined twice on unix.
+    let _ = instruct! { #[cfg(unix)] red: 0, #[cfg(unix)] red: 1 }; // `red` is defined twice on unix.
+    destruct! { let {red, #[cfg(unix)] red} = color }; // `red` is destructured twice on unix.
+    let _: Struct! { red: u8, #[cfg(unix)] red: u8 } = instruct! { red: 0 }; // `red` is declared twice on unix.
+
+    destruct! {fn mix({#[cfg(unix)] green: f64, green: f64}) -> f64 { green }}; // `green` is declared twice on unix.
+    let _ = call! { mix, { green: 1.0, #[cfg(unix)] green: 2.0 }}; // `green` is passed twice on unix.
+}
+
+
+fn main() {
+    test_struct_duplicate_cfg_fields();
+}
@@ -0,0 +1,16 @@
+
+fn test_struct_duplicate_fields() {
+    use obstruct_macros::{call, destruct, instruct, Struct};
+
+    let color = instruct! { red: 0, green: 1.0, red: 2, red: 3 }; // `red` is defined three times.
+    destruct! { let {red, green, r#red} = color }; // `r#red` is `red`.
+    let _: Struct! { red: u8, red: u8 } = instruct! { red: 0 }; // `red` is declared twice.
+
+    destruct! {fn mix({red: u8, green: f64, green: f64}) -> u8 { red }}; // `green` is declared twice.
+    let _ = call! { mix, { green: 1.0, red: 0, green: 2.0 }}; // `green` is passed twice.
+}
+
+
+fn main() {
+    test_struct_duplicate_fields();
+}
@@ -0,0 +1,12 @@
+
+fn test_struct_index_shorthand() {
+    use obstruct_macros::{destruct, instruct};
+
+    let pair = instruct! { 0: 'a', 1 }; // `1` needs a value.
+    destruct! { let {0, 1: b} = pair }; // `0` needs a pattern.
+}
+
+
+fn main() {
+    test_struct_index_shorthand();
+}
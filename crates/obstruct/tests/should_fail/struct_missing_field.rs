@@ -0,0 +1,8 @@
+fn struct_missing_field() {
+    use obstruct_macros::{instruct, destruct};
+
+    let structured = instruct! { red: 0, green: 1.0, blue: "blue" };
+
+    destruct! { let {red, green} = structured }; // We've forgotten blue.
+}
+fn main() {}
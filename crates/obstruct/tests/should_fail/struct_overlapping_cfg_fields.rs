@@ -0,0 +1,17 @@
+
+fn test_struct_overlapping_cfg_fields() {
+    use obstruct_macros::{call, destruct, instruct, Struct};
+
+    // `#[cfg(unix)]` and `#[cfg(target_os = "linux")]` both hold on Linux.
+    let color = instruct! { #[cfg(unix)] red: 0, #[cfg(target_os = "linux")] red: 1, green: 2 }; // `red` is defined twice on Linux.
+    destruct! { let {#[cfg(unix)] red, #[cfg(target_os = "linux")] red, green: _} = color }; // `red` is destructured twice on Linux.
+    let _: Struct! { #[cfg(unix)] red: u8, #[cfg(target_os = "linux")] red: u8 } = instruct! { red: 0 }; // `red` is declared twice on Linux.
+
+    destruct! {fn mix({#[cfg(unix)] green: f64, #[cfg(target_os = "linux")] green: f64}) -> f64 { green }}; // `green` is declared twice on Linux.
+    let _ = call! { mix, { #[cfg(unix)] green: 1.0, #[cfg(target_os = "linux")] green: 2.0 }}; // `green` is passed twice on Linux.
+}
+
+
+fn main() {
+    test_struct_overlapping_cfg_fields();
+}
@@ -1,5 +1,9 @@
 //! Tests for proc macros.
 
+// Fields of type `()` are compared and `Copy` structs are cloned on purpose, to check the
+// macros with any type and expression. `Struct!` types are complex by nature.
+#![allow(clippy::unit_cmp, clippy::clone_on_copy, clippy::type_complexity)]
+
 #[test]
 fn test_instruct_destruct_good() {
     // Check that we can define basic anonymous structs
@@ -192,3 +196,735 @@ fn test_instruct_call() {
     assert_eq!(b, B);
     assert_eq!(c, C);
 }
+
+#[test]
+fn test_instruct_call_shorthand() {
+    // Check that we can use the field init shorthand, mixed with
+    // regular bindings.
+    use obstruct_macros::{call, destruct, instruct};
+    const RED: &str = "red";
+    const GREEN: f64 = 1.0;
+    let red = RED;
+    let green = GREEN;
+
+    let structured = instruct! { red, green, blue: () };
+    destruct! { let {blue, green, red} = structured };
+    assert_eq!(red, RED);
+    assert_eq!(green, GREEN);
+    assert_eq!(blue, ());
+
+    destruct! {fn test_foo({a: i32, b: f64, c: &'static str}) -> (i32, f64, &'static str) { (a, b, c) }};
+
+    let a = 15;
+    let c = "Out of order";
+    let (a2, b2, c2) = call! { test_foo, { c, b: 17f64, a }};
+    assert_eq!(a2, a);
+    assert_eq!(b2, 17f64);
+    assert_eq!(c2, c);
+}
+
+#[test]
+fn test_instruct_call_paths() {
+    // Check that we can call functions by name or by path.
+    mod shapes {
+        use obstruct_macros::{call, destruct};
+        destruct! {fn area({width: u32, height: u32}) -> u32 { width * height }}
+
+        pub fn square(side: u32) -> u32 {
+            call! { area, { width: side, height: side }}
+        }
+        pub fn rectangle(width: u32, height: u32) -> u32 {
+            call! { self::area, { width, height }}
+        }
+    }
+    assert_eq!(shapes::square(3), 9);
+    assert_eq!(shapes::rectangle(2, 3), 6);
+}
+
+#[test]
+fn test_instruct_call_params_paths() {
+    // Check that we can call functions declared with `#[params]` by path, from outside their
+    // module, as far as they are visible.
+    mod shapes {
+        use obstruct_macros::destruct;
+        destruct! {#[params] pub fn area({width: u32, height: u32 = 1}) -> u32 { width * height }}
+
+        pub mod solids {
+            use obstruct_macros::destruct;
+            destruct! {#[params] pub(in super::super) fn volume({width: u32, height: u32, depth: u32 = 1}) -> u32 { width * height * depth }}
+        }
+
+        // The same function in another module, whose companion macro is exported separately.
+        pub mod flat {
+            use obstruct_macros::destruct;
+            destruct! {#[params] pub fn area({width: u32, height: u32 = 1}) -> u32 { width * height }}
+        }
+    }
+    use obstruct_macros::call;
+    assert_eq!(call! { #[params] shapes::area, { width: 2 }}, 2);
+    assert_eq!(call! { #[params] shapes::solids::volume, { width: 2, height: 3 }}, 6);
+    assert_eq!(call! { #[params] shapes::flat::area, { width: 3 }}, 3);
+}
+
+#[test]
+fn test_instruct_call_plain() {
+    // Check that `call!` calls functions not declared with `destruct!{ #[params] fn ... }`,
+    // and that `destruct!` declares functions wherever functions may be declared.
+    use obstruct::Struct;
+    use obstruct_macros::{call, destruct, instruct};
+
+    struct Shape;
+    impl Shape {
+        destruct! {fn area({width: u32, height: u32}) -> u32 { width * height }}
+    }
+    assert_eq!(call! { Shape::area, { width: 2, height: 3 }}, 6);
+
+    fn perimeter(shape: Struct! { width: u32, height: u32 }) -> u32 {
+        2 * (shape.0.0 + shape.1.0.0)
+    }
+    assert_eq!(call! { perimeter, { width: 2, height: 3 }}, 10);
+
+    let diagonal = |shape: Struct! { width: u32, height: u32 }| shape.0.0.max(shape.1.0.0);
+    assert_eq!(call! { diagonal, { width: 2, height: 3 }}, 3);
+    let pointer: fn(Struct! { width: u32, height: u32 }) -> u32 = perimeter;
+    assert_eq!(call! { pointer, { height: 3, width: 2 }}, 10);
+
+    // A module with the same name as a function declared with `#[params]`.
+    #[allow(dead_code)]
+    mod volume {}
+    destruct! {#[params] fn volume({width: u32, height: u32, depth: u32 = 1}) -> u32 { width * height * depth }}
+    assert_eq!(call! { #[params] volume, { width: 2, height: 3 }}, 6);
+    assert_eq!(volume(instruct! { width: 2, height: 3, depth: 4 }), 24);
+}
+
+#[test]
+fn test_instruct_call_defaults() {
+    // Check that `call!` fills in default values of functions declared with `#[params]`.
+    use obstruct_macros::{call, destruct, instruct};
+
+    destruct! {#[params] fn greet({name: &'static str, greeting: &'static str = "Hello", times: usize = 1}) -> String {
+        format!("{greeting}, {name}{}", "!".repeat(times))
+    }}
+
+    assert_eq!(call! { #[params] greet, { name: "world" }}, "Hello, world!");
+    assert_eq!(call! { #[params] greet, { times: 3, name: "world" }}, "Hello, world!!!");
+    assert_eq!(call! { #[params] greet, { greeting: "Bye", name: "world", times: 0 }}, "Bye, world");
+
+    // Without `call!`, all named arguments are required.
+    assert_eq!(greet(instruct! { greeting: "Hi", name: "you", times: 2 }), "Hi, you!!");
+
+    destruct! {#[params] fn area({width: f64 = 1.0, height: f64 = 1.0, ..}) -> f64 { width * height }};
+    assert_eq!(call! { #[params] area, {}}, 1.0);
+    assert_eq!(call! { #[params] area, { height: 3.0, color: "red" }}, 3.0);
+    // With `..`, other named arguments are accepted, even if close to a named argument.
+    assert_eq!(call! { #[params] area, { height: 3.0, heights: [1.0, 2.0] }}, 3.0);
+}
+
+#[test]
+fn test_instruct_destruct_nested() {
+    // Check that we can build and destructure nested anonymous structs.
+    use obstruct_macros::{destruct, instruct};
+
+    let x = 1;
+    let shape = instruct! { pos: { x, y: 2 }, color: "red", size: { 3 } };
+
+    destruct! { let ref { pos: { x, y }, color, size } = shape };
+    assert_eq!(x, &1);
+    assert_eq!(y, &2);
+    assert_eq!(color, &"red");
+    assert_eq!(size, &3);
+
+    // Fields of nested anonymous structs, with integer values, may be looked up by name.
+    let pos = obstruct::Has::<"pos", _, _>::get(&shape);
+    assert_eq!(obstruct::Has::<"y", _, _>::get(pos), &2);
+
+    destruct! { let { color, pos: { y: another_y, x }, size: _ } = shape };
+    assert_eq!(x, 1);
+    assert_eq!(another_y, 2);
+    assert_eq!(color, "red");
+}
+
+#[test]
+fn test_instruct_call_rest() {
+    // Check that functions declared with `..` accept anonymous structs with extra fields.
+    use obstruct_macros::{call, destruct, instruct};
+
+    destruct! {fn area({width: f64, height: f64, ..}) -> f64 { width * height }};
+
+    assert_eq!(call! { area, { width: 2.0, height: 3.0 }}, 6.0);
+    assert_eq!(call! { area, { color: "red", height: 3.0, width: 2.0, depth: 4 }}, 6.0);
+    assert_eq!(area(instruct! { height: 3.0, name: "box", width: 2.0 }), 6.0);
+
+    // Or, equivalently, without `destruct!`.
+    fn perimeter<I, J>(s: impl obstruct::Has<"width", f64, I> + obstruct::Has<"height", f64, J> + Clone) -> f64 {
+        2.0 * (obstruct::Has::<"width", f64, I>::take(s.clone()) + obstruct::Has::<"height", f64, J>::take(s))
+    }
+    assert_eq!(perimeter(instruct! { color: "red", height: 3.0, width: 2.0 }), 10.0);
+    assert_eq!(perimeter(instruct! { height: 3.0, width: 2.0 }), 10.0);
+}
+
+#[test]
+fn test_has_get() {
+    // Check that generic code can access fields by name.
+    use obstruct::Has;
+    use obstruct_macros::instruct;
+
+    fn bump_id<I>(s: &mut impl Has<"id", u64, I>) -> u64 {
+        *s.get_mut() += 1;
+        *s.get()
+    }
+
+    let mut user = instruct! { name: "Alice", id: 41 };
+    assert_eq!(bump_id(&mut user), 42);
+    assert_eq!(Has::<"id", u64, _>::take(user), 42);
+
+    let mut item = instruct! { id: 0 };
+    assert_eq!(bump_id(&mut item), 1);
+}
+
+#[test]
+fn test_reflect() {
+    // Check that we can inspect the fields of an anonymous struct.
+    use std::fmt::Debug;
+    use obstruct::{AnonymousStruct, Struct, Visit, Visitor};
+    use obstruct_macros::instruct;
+
+    let structured = instruct! { red: 0u8, green: 1.0, blue: "blue" };
+
+    fn field_names<S: AnonymousStruct>(_: &S) -> (&'static [&'static str], usize) {
+        (S::FIELD_NAMES, S::LEN)
+    }
+    assert_eq!(field_names(&structured), (&["blue", "green", "red"][..], 3));
+    assert_eq!(field_names(&instruct! {}), (&[][..], 0));
+    const NAMES: &[&str] = <Struct! { x: u8, y: u8 } as AnonymousStruct>::FIELD_NAMES;
+    assert_eq!(NAMES, ["x", "y"]);
+
+    let mut reds = vec![];
+    structured.for_each_field(|name, value| {
+        if let Some(red) = value.downcast_ref::<u8>() {
+            reds.push((name, *red));
+        }
+    });
+    assert_eq!(reds, vec![("red", 0)]);
+
+    // A statically typed visitor.
+    struct Logger(Vec<String>);
+    impl<T: Debug> Visitor<T> for Logger {
+        fn visit(&mut self, name: &'static str, value: &T) {
+            self.0.push(format!("{name}: {value:?}"));
+        }
+    }
+    let mut logger = Logger(vec![]);
+    structured.visit(&mut logger);
+    assert_eq!(logger.0, vec!["blue: \"blue\"", "green: 1.0", "red: 0"]);
+}
+
+#[test]
+fn test_struct_type() {
+    // Check that we can name anonymous struct types and destructure their values.
+    use obstruct::{Named, Struct};
+    use obstruct_macros::destruct;
+
+    // Fields are a list, in canonical (alphabetical) order.
+    let structured: Struct! { red: u8, pos: { x: i32, y: i32 } } = (
+        Named((Named(1), (Named(2), ()))),
+        (Named(0), ()),
+    );
+    destruct! { let {red, pos: {x, y}} = structured };
+    assert_eq!(red, 0);
+    assert_eq!((x, y), (1, 2));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    // Check that anonymous structs serialize as maps and deserialize in any order.
+    use obstruct::{AsMap, Struct};
+    use obstruct_macros::{destruct, instruct};
+
+    let structured = instruct! { red: 0, green: 1.5, blue: "blue" };
+    let json = serde_json::to_string(&AsMap(structured)).unwrap();
+    assert_eq!(json, r#"{"blue":"blue","green":1.5,"red":0}"#);
+
+    let AsMap(structured): AsMap<Struct! { red: u8, green: f64, blue: String }> =
+        serde_json::from_str(r#"{"red":0,"blue":"blue","green":1.5,"extra":null}"#).unwrap();
+    destruct! { let {red, green, blue} = structured };
+    assert_eq!(red, 0);
+    assert_eq!(green, 1.5);
+    assert_eq!(blue, "blue");
+
+    let missing = serde_json::from_str::<AsMap<Struct! { red: u8, green: f64 }>>(r#"{"red":0}"#);
+    assert!(missing.unwrap_err().to_string().contains("missing field `green`"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_nested() {
+    // Check that nested anonymous structs serialize as maps once wrapped in `AsMap`, including
+    // within collections.
+    use obstruct::{AsMap, Struct};
+    use obstruct_macros::{destruct, instruct};
+
+    let shape = instruct! { pos: AsMap(instruct! { x: 1, y: 2 }), size: 3 };
+    let json = serde_json::to_string(&AsMap(shape)).unwrap();
+    assert_eq!(json, r#"{"pos":{"x":1,"y":2},"size":3}"#);
+
+    let AsMap(shape): AsMap<Struct! { pos: AsMap<Struct! { x: u8, y: u8 }>, size: u8 }> =
+        serde_json::from_str(&json).unwrap();
+    destruct! { let {pos: AsMap(pos), size} = shape };
+    destruct! { let {x, y} = pos };
+    assert_eq!((x, y, size), (1, 2, 3));
+
+    let colors: Vec<_> = (0..2).map(|i| AsMap(instruct! { red: i })).collect();
+    let json = serde_json::to_string(&colors).unwrap();
+    assert_eq!(json, r#"[{"red":0},{"red":1}]"#);
+    let colors: Vec<AsMap<Struct! { red: u8 }>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(colors.len(), 2);
+}
+
+#[test]
+fn test_derive() {
+    // Check that we can convert between named structs and anonymous structs.
+    use obstruct::{Obstruct, Struct};
+    use obstruct_macros::{destruct, instruct};
+
+    #[derive(Obstruct, Debug, PartialEq)]
+    struct Color<T> {
+        red: T,
+        green: f64,
+    }
+
+    destruct! { let {red, green} = Color { red: 0u8, green: 1.0 } };
+    assert_eq!(red, 0);
+    assert_eq!(green, 1.0);
+
+    let color: Color<u8> = instruct! { green: 1.0, red: 0 }.into();
+    assert_eq!(color, Color { red: 0, green: 1.0 });
+
+    let anonymous: Struct! { red: u8, green: f64 } = color.into();
+    let color: Color<u8> = anonymous.into();
+    assert_eq!(color, Color { red: 0, green: 1.0 });
+}
+
+#[test]
+fn test_derive_generic_defaults() {
+    // Check that generic structs with default types, lifetimes and where clauses may derive
+    // `Obstruct`.
+    use obstruct::{Obstruct, Struct};
+    use obstruct_macros::instruct;
+
+    #[derive(Obstruct, Debug, PartialEq)]
+    struct Label<'a, T = u8, const N: usize = 2> where T: Copy {
+        text: &'a str,
+        sizes: [T; N],
+    }
+
+    let label: Label = instruct! { text: "label", sizes: [1, 2] }.into();
+    assert_eq!(label, Label { text: "label", sizes: [1, 2] });
+
+    let anonymous: Struct! { text: &str, sizes: [u8; 2] } = label.into();
+    let label: Label<'_, u8> = anonymous.into();
+    assert_eq!(label.sizes, [1, 2]);
+}
+
+#[test]
+fn test_tuple() {
+    // Check that we can convert anonymous structs from and to tuples in canonical order.
+    use obstruct::{FromTuple, IntoTuple, Struct};
+    use obstruct_macros::{destruct, instruct};
+
+    assert_eq!(instruct! { red: 0, green: 1.0, blue: "blue" }.into_tuple(), ("blue", 1.0, 0));
+
+    let colors = (0..2).map(|i| instruct! { red: i * 3, green: i + 1 });
+    let (greens, reds): (Vec<_>, Vec<_>) = colors.map(IntoTuple::into_tuple).unzip();
+    assert_eq!(greens, vec![1, 2]);
+    assert_eq!(reds, vec![0, 3]);
+
+    let mut colors = greens.into_iter().zip(reds).map(<Struct! { red: u8, green: u8 }>::from_tuple);
+    destruct! { let {red, green} = colors.next().unwrap() };
+    assert_eq!((red, green), (0, 1));
+}
+
+#[test]
+fn test_many_fields() {
+    // Check that anonymous structs with more than twelve fields keep their traits.
+    use obstruct::{AnonymousStruct, Has};
+    use obstruct_macros::{destruct, instruct};
+
+    let make = |n: u32| instruct! {
+        a: n, b: 1, c: 2, d: 3, e: 4, f: 5, g: 6, h: 7,
+        i: 8, j: 9, k: 10, l: 11, m: 12, n: 13, o: 14, p: 15,
+    };
+    let structured = make(0);
+    assert_eq!(structured.clone(), make(0));
+    assert_ne!(structured, make(1));
+    assert!(structured < make(1));
+    assert!(format!("{structured:?}").contains("15"));
+
+    let mut hashes = std::collections::HashSet::new();
+    hashes.insert(make(0));
+    assert!(hashes.contains(&structured));
+
+    fn default_like<T: Default>(_: &T) -> T {
+        T::default()
+    }
+    assert_eq!(*Has::<"p", u32, _>::get(&default_like(&structured)), 0);
+
+    fn names<S: AnonymousStruct>(_: &S) -> &'static [&'static str] {
+        S::FIELD_NAMES
+    }
+    assert_eq!(names(&structured).len(), 16);
+    assert_eq!(names(&structured)[15], "p");
+
+    let mut sum = 0;
+    structured.for_each_field(|_, value| sum += value.downcast_ref::<u32>().copied().unwrap_or(0));
+    assert_eq!(sum, 120);
+
+    destruct! { let {p, o, n, m, l, k, j, i, h, g, f, e, d, c, b, a} = structured };
+    assert_eq!([a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p], [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+}
+
+#[test]
+fn test_instruct_type() {
+    // Check that values created by distinct uses of `instruct!` share their type.
+    use obstruct::Struct;
+    use obstruct_macros::{destruct, instruct};
+
+    let colors: Vec<Struct! { red: u8, green: u8 }> = vec![
+        instruct! { red: 0, green: 1 },
+        instruct! { green: 2, red: 3 },
+    ];
+    let reds: Vec<u8> = colors.into_iter().map(|color| {
+        destruct! { let {red, green: _} = color };
+        red
+    }).collect();
+    assert_eq!(reds, vec![0, 3]);
+}
+
+#[test]
+fn test_merge() {
+    // Check that anonymous structs may be merged, in canonical order, that the result may be
+    // destructured, and that the right-hand side wins with `merge_override!`.
+    use obstruct::{merge, merge_override, Struct};
+    use obstruct_macros::{destruct, instruct};
+
+    let merged = merge!(instruct! { red: 0, blue: 2 }, instruct! { green: 1 });
+    destruct! { let {red, green, blue} = merged };
+    assert_eq!((red, green, blue), (0, 1, 2));
+
+    let merged: Struct! { red: u8, green: u8, blue: u8 } = merge!(instruct! { red: 0 }, instruct! { green: 1, blue: 2 });
+    assert_eq!(merged, instruct! { green: 1, red: 0, blue: 2 });
+    assert_eq!(merge!(instruct! { red: 0 }, instruct! { blue: 2 }), instruct! { red: 0, blue: 2 });
+    assert_eq!(merge!(instruct! {}, instruct! { a: 0 }), instruct! { a: 0 });
+    assert_eq!(merge!(instruct! { a: 0 }, instruct! {}), instruct! { a: 0 });
+
+    // The right-hand side wins, possibly with a different type, and may add fields.
+    let defaults = instruct! { verbose: false, retries: 3, name: "default" };
+    let config = instruct! { retries: 5, color: true };
+    let cli = instruct! { verbose: true, name: String::from("cli") };
+    let options = merge_override!(merge_override!(defaults, config), cli);
+    assert_eq!(options, instruct! { verbose: true, retries: 5, name: String::from("cli"), color: true });
+}
+
+#[test]
+fn test_destruct_any_order() {
+    // Check that `destruct!` doesn't depend on the order of fields, including by reference,
+    // and that fields may be put back in canonical order.
+    use obstruct::{Named, Reorder, Struct};
+    use obstruct_macros::{destruct, instruct};
+
+    let mut shuffled = (Named::<"y", _>(1), (Named::<"z", _>(2), (Named::<"x", _>(0), ())));
+    {
+        destruct! { let {ref mut x, ref y, ref z} = shuffled };
+        *x += *y + *z;
+    }
+    let reordered: Struct! { x: i32, y: i32, z: i32 } = shuffled.reorder();
+    assert_eq!(reordered, instruct! { x: 3, y: 1, z: 2 });
+    destruct! { let {x, y: _, z: _} = reordered };
+    assert_eq!(x, 3);
+
+    // Fields may have generic types.
+    fn swap<A, B>(pair: Struct! { a: A, b: B }) -> Struct! { a: B, b: A } {
+        destruct! { let {b, a} = pair };
+        instruct! { a: b, b: a }
+    }
+    assert_eq!(swap(instruct! { a: 1, b: "b" }), instruct! { a: "b", b: 1 });
+}
+
+#[test]
+fn test_pick_omit() {
+    // Check that we can select some fields of an anonymous struct.
+    use obstruct::{merge, Struct};
+    use obstruct_macros::{instruct, omit, pick};
+
+    let color = instruct! { red: 0, green: 1.0, blue: "blue" };
+    let picked: Struct! { red: u8, blue: &str } = pick!(color, {red, blue});
+    assert_eq!(picked, instruct! { red: 0, blue: "blue" });
+
+    let color = instruct! { red: 0, green: 1.0, blue: "blue" };
+    let omitted: Struct! { red: u8, blue: &str } = omit!(color, {green});
+    assert_eq!(omitted, picked);
+
+    // The order of fields doesn't matter.
+    let merged = merge!(instruct! { red: 0, green: 1.0 }, instruct! { blue: "blue" });
+    assert_eq!(pick!(merged, {blue, red}), picked);
+}
+
+#[test]
+fn test_rename() {
+    // Check that we can rename fields, in canonical order, including by swapping their names.
+    use obstruct::Struct;
+    use obstruct_macros::{destruct, instruct, rename};
+
+    let shape = instruct! { colour: "red", sz: 3, pos: (0, 0) };
+    let renamed: Struct! { color: &str, size: u32, pos: (i32, i32) } = rename!(shape, {colour => color, sz => size});
+    assert_eq!(renamed, instruct! { color: "red", size: 3, pos: (0, 0) });
+    assert_eq!(rename!(instruct! { a: 0, b: 1 }, {a => c}), instruct! { b: 1, c: 0 });
+
+    let swapped = rename!(instruct! { x: 1, y: 2 }, {x => y, y => x});
+    destruct! { let {x, y} = swapped };
+    assert_eq!((x, y), (2, 1));
+}
+
+#[test]
+fn test_map() {
+    // Check that we can transform the value of one field or of all fields.
+    use obstruct::{MapAll, Struct};
+    use obstruct_macros::{instruct, map_field};
+
+    let color = instruct! { red: 255u8, green: 1.0f32, blue: 0.5f32 };
+    let mapped: Struct! { red: f32, green: f32, blue: f32 } = map_field!(color, red => |r| r as f32 / 255.0);
+    assert_eq!(mapped, instruct! { red: 1.0, green: 1.0, blue: 0.5 });
+
+    let names = mapped.map_all(|value| value.to_string());
+    assert_eq!(names, instruct! { red: "1".to_string(), green: "1".to_string(), blue: "0.5".to_string() });
+
+    let lengths = map_field!(instruct! { name: "obstruct" }, name => |name| name.len());
+    assert_eq!(lengths, instruct! { name: 8 });
+}
+
+#[test]
+fn test_set() {
+    // Check that we can set a field in place.
+    use std::time::Duration;
+    use obstruct_macros::{instruct, replace, set};
+
+    struct Client<T> {
+        config: T,
+    }
+
+    let mut client = Client { config: instruct! { timeout: Duration::from_secs(1), retries: 3 } };
+    set!(client.config.timeout = Duration::from_secs(5));
+    let previous = replace!(client.config.retries = 5);
+    assert_eq!(previous, 3);
+    assert_eq!(client.config, instruct! { timeout: Duration::from_secs(5), retries: 5 });
+
+    // Through references, as with regular structs.
+    fn reset(config: &mut obstruct::Struct! { timeout: Duration, retries: u8 }) -> u8 {
+        set!(config.timeout = Duration::ZERO);
+        replace!(config.retries = 0)
+    }
+    assert_eq!(reset(&mut client.config), 5);
+    let config = &mut &mut client.config;
+    set!(config.retries = 1);
+    assert_eq!(client.config, instruct! { timeout: Duration::ZERO, retries: 1 });
+}
+
+#[test]
+fn test_hygiene() {
+    // Check that the names used by generated code don't collide with those of user code.
+    use obstruct_macros::{call, destruct, instruct, pick, rename};
+
+    // Named arguments with the names of internal variables.
+    destruct! {fn sum({args: u32, obstruct_args: u32}) -> u32 { args + obstruct_args }}
+    assert_eq!(call! { sum, { args: 1, obstruct_args: 2 }}, 3);
+
+    // A type named `Field` in scope of the body of a function.
+    #[allow(dead_code)]
+    struct Field(u8);
+    destruct! {fn wrap({value: u8}) -> u8 { let Field(value) = Field(value); value }}
+    assert_eq!(call! { wrap, { value: 4 }}, 4);
+
+    // Recursion, by name.
+    destruct! {fn factorial({n: u64}) -> u64 { if n == 0 { 1 } else { n * call! { factorial, { n: n - 1 }} } }}
+    assert_eq!(call! { factorial, { n: 5 }}, 120);
+
+    // A field with the name of a type, whose value uses a variable with the same name.
+    #[allow(non_camel_case_types, dead_code)]
+    struct x {}
+    let x = 1;
+    let point = instruct! { x: x + 1, y: x };
+    destruct! { let {x, y} = point };
+    assert_eq!((x, y), (2, 1));
+
+    // Variables with the names of internal variables.
+    let obstruct_struct_0 = 3;
+    let obstruct_0_a = 4;
+    let obstruct_struct = 5;
+    destruct! { let {a, b} = instruct! { a: obstruct_struct_0, b: obstruct_0_a } };
+    assert_eq!((a, b), (3, 4));
+    let picked = pick!(instruct! { a: obstruct_struct, b: 0 }, { a, });
+    assert_eq!(picked, instruct! { a: 5 });
+    let renamed = rename!(instruct! { a: obstruct_struct }, { a => obstruct_a });
+    assert_eq!(renamed, instruct! { obstruct_a: 5 });
+
+    // Types and generic types named like internal generic types, i.e. `__Obstruct{HASH}Args`.
+    #[allow(non_camel_case_types)]
+    fn generic_names() {
+        #[derive(Debug, PartialEq)]
+        struct __ObstructArgs(u8);
+        destruct! {fn unwrap({args: __ObstructArgs}) -> u8 { args.0 }}
+        assert_eq!(call! { unwrap, { args: __ObstructArgs(6) }}, 6);
+        type __ObstructRest1 = u8;
+        destruct! {fn first<__ObstructIndex0>({a: __ObstructIndex0, b: __ObstructRest1}) -> __ObstructIndex0 { let _ = b; a }}
+        assert_eq!(call! { first, { a: __ObstructArgs(7), b: 8 }}, __ObstructArgs(7));
+
+        // Identical functions, whose generic types are named after distinct hashes.
+        mod left {
+            obstruct_macros::destruct! {#[params] pub fn side({a: u8, b: u8 = 1}) -> u8 { a + b }}
+        }
+        mod right {
+            obstruct_macros::destruct! {#[params] pub fn side({a: u8, b: u8 = 1}) -> u8 { a + b }}
+        }
+        assert_eq!(call! { #[params] left::side, { a: 8 }} + call! { #[params] right::side, { a: 9, b: 0 }}, 18);
+    }
+    generic_names();
+}
+
+#[test]
+fn test_reexport() {
+    // Check that the macros work through a renamed re-export of `obstruct`.
+    mod facade {
+        pub use ::obstruct as anonymous;
+    }
+    use facade::anonymous::{call, destruct, instruct, pick, Obstruct, Struct};
+
+    #[derive(Obstruct, Debug, PartialEq)]
+    #[obstruct(crate = facade::anonymous)]
+    struct Point {
+        x: u32,
+        y: u32,
+    }
+
+    destruct! {#[params] fn norm1({x: u32, y: u32 = 0}) -> u32 { x + y }}
+    assert_eq!(call! { #[params] norm1, { x: 1 }}, 1);
+
+    let point: Point = instruct! { y: 2, x: 1 }.into();
+    assert_eq!(point, Point { x: 1, y: 2 });
+    let anonymous: Struct! { x: u32, y: u32 } = point.into();
+    assert_eq!(pick!(anonymous, { y }), instruct! { y: 2 });
+}
+
+#[test]
+fn test_field_names() {
+    // Check that fields may be named by indices, raw identifiers and non-ASCII identifiers.
+    use obstruct::{AnonymousStruct, Obstruct, Struct};
+    use obstruct_macros::{call, destruct, instruct, pick, rename, set};
+
+    // Indices, as in tuple structs.
+    let mut pair: Struct! { 0: u8, 1: &str } = instruct! { 1: "one", 0: 0 };
+    set!(pair.0 = 1);
+    destruct! { let {0: first, 1: second} = pair };
+    assert_eq!((first, second), (1, "one"));
+    let renamed = rename!(instruct! { 0: 0, 1: 1 }, { 0 => x, 1 => y });
+    assert_eq!(renamed, instruct! { x: 0, y: 1 });
+    let nested = instruct! { inner: { 0: 'a', }, 10: 10 };
+    assert_eq!(pick!(nested, { 10 }), instruct! { 10: 10 });
+
+    // Raw identifiers are the same names as bare identifiers.
+    let r#type = "keyword";
+    let keywords = instruct! { r#type, r#match: 1 };
+    assert_eq!(<Struct! { r#match: u8, r#type: &str } as AnonymousStruct>::FIELD_NAMES, ["match", "type"]);
+    destruct! { let {r#match, r#type: kind} = keywords };
+    assert_eq!((r#match, kind), (1, "keyword"));
+
+    #[derive(Obstruct, Debug, PartialEq)]
+    struct Token {
+        r#type: u8,
+        r#ref: u8,
+    }
+    let token: Token = instruct! { r#ref: 1, r#type: 2 }.into();
+    assert_eq!(token, Token { r#type: 2, r#ref: 1 });
+
+    // Raw identifiers with the names of internal variables.
+    let s = instruct! { #[cfg(all())] r#struct: 1, other: 2 };
+    assert_eq!(pick!(s, { r#struct }), instruct! { r#struct: 1 });
+    destruct! { let {x, other} = rename!(s, { r#struct => x }) };
+    assert_eq!((x, other), (1, 2));
+
+    // Non-ASCII identifiers.
+    let météo = instruct! { température: 30, durée: 2 };
+    destruct! { let {durée, température} = météo };
+    assert_eq!((température, durée), (30, 2));
+
+    destruct! {#[params] fn describe({r#type: &'static str, taille: u32 = 1}) -> String { format!("{} {}", r#type, taille) }}
+    assert_eq!(call! { #[params] describe, { r#type: "box" }}, "box 1");
+    assert_eq!(call! { #[params] describe, { taille: 2, r#type: "box" }}, "box 2");
+}
+
+#[test]
+fn test_cfg() {
+    // Check that fields and named arguments disabled by `#[cfg]` disappear.
+    use obstruct::Struct;
+    use obstruct_macros::{call, destruct, instruct};
+
+    let point: Struct! { x: u8 } = instruct! { #[cfg(any())] y: 2, x: 1 };
+    assert_eq!(point, instruct! { x: 1 });
+    let point: Struct! { x: u8, y: u8 } = instruct! { #[cfg(all())] y: 2, x: 1 };
+    assert_eq!(point, instruct! { x: 1, y: 2 });
+    let nested = instruct! { pos: { #[cfg(any())] z: 3, x: 1 } };
+    assert_eq!(nested, instruct! { pos: { x: 1, } });
+
+    // The same applies to the types of anonymous structs.
+    let point: Struct! { #[cfg(any())] y: u8, x: u8 } = instruct! { #[cfg(any())] y: 2, x: 1 };
+    assert_eq!(point, instruct! { x: 1 });
+    let point: Struct! { #[cfg(all())] y: u8, x: u8 } = instruct! { #[cfg(all())] y: 2, x: 1 };
+    assert_eq!(point, instruct! { x: 1, y: 2 });
+
+    // And to the fields of patterns.
+    destruct! { let {#[cfg(all())] x, #[cfg(any())] z, y} = instruct! { x: 1, y: 2, #[cfg(any())] z: 3 } };
+    assert_eq!((x, y), (1, 2));
+    destruct! { let {pos: {#[cfg(all())] x: nested_x}} = nested };
+    assert_eq!(nested_x, 1);
+
+    // A field may have alternative definitions.
+    let platform = instruct! { #[cfg(unix)] name: "unix", #[cfg(not(unix))] name: "other" };
+    destruct! { let {name} = platform };
+    assert_eq!(name, if cfg!(unix) { "unix" } else { "other" });
+
+    destruct! {fn open({#[cfg(any())] fd: i32, path: &'static str}) -> &'static str { path }}
+    assert_eq!(call! { open, { path: "/tmp" }}, "/tmp");
+    assert_eq!(call! { open, { #[cfg(any())] fd: 3, path: "/tmp" }}, "/tmp");
+    destruct! {fn open_fd({#[cfg(all())] fd: i32, path: &'static str}) -> (i32, &'static str) { (fd, path) }}
+    assert_eq!(call! { open_fd, { fd: 3, path: "/tmp" }}, (3, "/tmp"));
+
+    // Named arguments with the same `#[cfg]` are enabled together.
+    destruct! {fn resize({#[cfg(all())] width: u32, #[cfg(all())] height: u32, #[cfg(any())] depth: u32, scale: u32}) -> u32 { (width + height) * scale }}
+    assert_eq!(call! { resize, { width: 1, height: 2, scale: 3 }}, 9);
+
+    // The default value applies if the argument is disabled.
+    destruct! {#[params] fn retries({count: u8 = 3}) -> u8 { count }}
+    assert_eq!(call! { #[params] retries, { #[cfg(any())] count: 5 }}, 3);
+    assert_eq!(call! { #[params] retries, { #[cfg(all())] count: 5 }}, 5);
+
+    // Other attributes apply to the variable.
+    destruct! {fn ignore({#[allow(unused_variables)] unused: u8}) {}}
+    call! { ignore, { unused: 0 }};
+}
+
+#[test]
+fn test_named_arg_attrs() {
+    // Check that named arguments may be passed by an alias, or be deprecated.
+    use obstruct_macros::{call, destruct};
+
+    destruct! {#[params] fn paint({#[alias(colour)] color: &'static str, #[alias(w, thickness)] width: u8 = 1}) -> (&'static str, u8) { (color, width) }}
+    assert_eq!(call! { #[params] paint, { color: "red" }}, ("red", 1));
+    assert_eq!(call! { #[params] paint, { colour: "red", thickness: 2 }}, ("red", 2));
+    assert_eq!(call! { #[params] paint, { w: 3, colour: "blue" }}, ("blue", 3));
+
+    destruct! {#[params] fn resize({width: u32, #[deprecated(note = "use `width`")] size: u32 = 0}) -> u32 { width + size }}
+    assert_eq!(call! { #[params] resize, { width: 1 }}, 1);
+    #[allow(deprecated)]
+    let size = call! { #[params] resize, { width: 1, size: 2 }};
+    assert_eq!(size, 3);
+}